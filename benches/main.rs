@@ -4,6 +4,8 @@ use tokio::runtime::{Builder};
 
 mod regular;
 pub use regular::*;
+mod queue;
+pub use queue::*;
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("async_mutex", |b| {
@@ -31,5 +33,33 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, criterion_benchmark);
+pub fn queue_benchmark(c: &mut Criterion) {
+    c.bench_function("uncontended_intrusive", |b| {
+        let mutex = async_mutex::movable::MovableMutex::new();
+        b.iter(|| uncontended_intrusive(&mutex))
+    });
+
+    c.bench_function("uncontended_legacy", |b| {
+        let mutex = LegacyMutex::new();
+        b.iter(|| uncontended_legacy(&mutex))
+    });
+
+    c.bench_function("contended_intrusive", |b| {
+        let runtime = Builder::new_multi_thread().build().unwrap();
+        b.to_async(runtime).iter(move || {
+            let mutex = Arc::new(async_mutex::movable::MovableMutex::new());
+            contended_intrusive(mutex)
+        })
+    });
+
+    c.bench_function("contended_legacy", |b| {
+        let runtime = Builder::new_multi_thread().build().unwrap();
+        b.to_async(runtime).iter(move || {
+            let mutex = Arc::new(LegacyMutex::new());
+            contended_legacy(mutex)
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark, queue_benchmark);
 criterion_main!(benches);
\ No newline at end of file
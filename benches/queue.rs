@@ -0,0 +1,157 @@
+use std::{sync::{Arc, atomic::{AtomicBool, Ordering}}, collections::VecDeque, cell::UnsafeCell, task::{Context, Poll, Waker}, future::Future, pin::Pin};
+use futures::future::try_join_all;
+use async_mutex::movable::MovableMutex;
+
+const SIZE : usize = 100;
+
+/// Next key, and the waiters with their keys
+type Entries = (usize, VecDeque<(usize, Option<Waker>)>);
+
+/// Mutex built on the spin-locked ```VecDeque``` waiter queue that was used before the intrusive list, kept as a baseline
+pub struct LegacyMutex {
+    locked: AtomicBool,
+    queue_locked: AtomicBool,
+    queue: UnsafeCell<Entries>
+}
+
+impl LegacyMutex {
+    pub const fn new () -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            queue_locked: AtomicBool::new(false),
+            queue: UnsafeCell::new((0, VecDeque::new()))
+        }
+    }
+
+    #[inline]
+    pub fn try_lock (&self) -> bool {
+        self.locked.compare_exchange(false, true, Ordering::Acquire, Ordering::Acquire).is_ok()
+    }
+
+    #[inline]
+    pub fn lock (&self) -> LegacyMutexFuture<'_> {
+        LegacyMutexFuture { mutex: self, key: None }
+    }
+
+    /// Always takes the queue's lock, even if nobody is waiting
+    #[inline]
+    pub fn unlock (&self) {
+        let waker = self.with(|(_, queue)| {
+            self.locked.store(false, Ordering::Release);
+            queue.front_mut().and_then(|x| x.1.take())
+        });
+
+        if let Some(waker) = waker {
+            waker.wake()
+        }
+    }
+
+    #[inline]
+    fn with<R, F: FnOnce(&mut Entries) -> R> (&self, f: F) -> R {
+        while self.queue_locked.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire).is_err() { std::hint::spin_loop() }
+        let result = f(unsafe { &mut *self.queue.get() });
+        self.queue_locked.store(false, Ordering::Release);
+        result
+    }
+
+    #[inline]
+    fn remove (&self, key: usize) -> bool {
+        self.with(|(_, queue)| {
+            let idx = queue.iter().position(|x| x.0 == key).unwrap();
+            queue.remove(idx).unwrap().1.is_none()
+        })
+    }
+}
+
+impl Default for LegacyMutex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl Send for LegacyMutex {}
+unsafe impl Sync for LegacyMutex {}
+
+pub struct LegacyMutexFuture<'a> {
+    mutex: &'a LegacyMutex,
+    key: Option<usize>
+}
+
+impl<'a> Future for LegacyMutexFuture<'a> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if !this.mutex.try_lock() {
+            let waker = cx.waker().clone();
+            this.mutex.with(|(next_key, queue)| match this.key {
+                Some(key) => queue.iter_mut().find(|x| x.0 == key).unwrap().1 = Some(waker),
+                None => {
+                    this.key = Some(*next_key);
+                    queue.push_back((*next_key, Some(waker)));
+                    *next_key += 1;
+                }
+            });
+
+            if !this.mutex.try_lock() {
+                return Poll::Pending
+            }
+        }
+
+        if let Some(key) = this.key.take() {
+            this.mutex.remove(key);
+        }
+        Poll::Ready(())
+    }
+}
+
+impl<'a> Drop for LegacyMutexFuture<'a> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            if self.mutex.remove(key) {
+                if let Some(waker) = self.mutex.with(|(_, queue)| queue.front_mut().and_then(|x| x.1.take())) {
+                    waker.wake()
+                }
+            }
+        }
+    }
+}
+
+pub fn uncontended_intrusive (mutex: &MovableMutex) {
+    mutex.try_lock().unwrap().unlock()
+}
+
+pub fn uncontended_legacy (mutex: &LegacyMutex) {
+    assert!(mutex.try_lock());
+    mutex.unlock()
+}
+
+pub async fn contended_intrusive (mutex: Arc<MovableMutex>) {
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for _ in 0..SIZE {
+        let mutex = mutex.clone();
+        handles.push(tokio::spawn(async move {
+            let guard = mutex.lock().await;
+            tokio::task::yield_now().await;
+            guard.unlock()
+        }));
+    }
+
+    try_join_all(handles).await.unwrap();
+}
+
+pub async fn contended_legacy (mutex: Arc<LegacyMutex>) {
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for _ in 0..SIZE {
+        let mutex = mutex.clone();
+        handles.push(tokio::spawn(async move {
+            mutex.lock().await;
+            tokio::task::yield_now().await;
+            mutex.unlock()
+        }));
+    }
+
+    try_join_all(handles).await.unwrap();
+}
@@ -0,0 +1,94 @@
+cfg_if::cfg_if! {
+    if #[cfg(all(feature = "std", any(feature = "stats", feature = "tracing")))] {
+        extern crate std;
+        use core::{sync::atomic::{AtomicU64, Ordering}, cell::Cell};
+        use std::{sync::OnceLock, time::Instant};
+
+        static EPOCH : OnceLock<Instant> = OnceLock::new();
+
+        /// Nanoseconds
+        pub(crate) type Nanos = u64;
+
+        /// When a waiter started waiting. Only ever accessed by the waiter.
+        pub(crate) struct Stamp (Cell<u64>);
+
+        /// When a mutex was acquired (offset by one), or zero if nobody is timing a hold
+        pub(crate) struct AtomicStamp (AtomicU64);
+
+        /// Nanoseconds since the first time it was called
+        #[inline(always)]
+        fn now () -> u64 {
+            EPOCH.get_or_init(Instant::now).elapsed().as_nanos() as u64
+        }
+
+        impl Stamp {
+            #[inline(always)]
+            pub const fn new () -> Self {
+                Self(Cell::new(0))
+            }
+
+            #[inline(always)]
+            pub fn start (&self) {
+                self.0.set(now())
+            }
+
+            #[inline(always)]
+            pub fn elapsed (&self) -> Nanos {
+                now().saturating_sub(self.0.get())
+            }
+        }
+
+        impl AtomicStamp {
+            #[inline(always)]
+            pub const fn new () -> Self {
+                Self(AtomicU64::new(0))
+            }
+
+            #[inline(always)]
+            pub fn start (&self) {
+                self.0.store(now() + 1, Ordering::Relaxed)
+            }
+
+            /// Returns the time since [```start```](AtomicStamp::start) was called, unless it has already been taken
+            #[inline(always)]
+            pub fn take (&self) -> Option<Nanos> {
+                match self.0.swap(0, Ordering::Relaxed) {
+                    0 => None,
+                    start => Some((now() + 1).saturating_sub(start))
+                }
+            }
+        }
+    } else {
+        pub(crate) type Nanos = ();
+        pub(crate) struct Stamp;
+        pub(crate) struct AtomicStamp;
+
+        impl Stamp {
+            #[inline(always)]
+            pub const fn new () -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn start (&self) {}
+
+            #[inline(always)]
+            pub fn elapsed (&self) -> Nanos {}
+        }
+
+        impl AtomicStamp {
+            #[inline(always)]
+            pub const fn new () -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn start (&self) {}
+
+            #[inline(always)]
+            pub fn take (&self) -> Option<Nanos> {
+                None
+            }
+        }
+    }
+}
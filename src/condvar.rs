@@ -0,0 +1,112 @@
+use core::{fmt::Debug, ops::DerefMut, pin::Pin};
+use crate::{guards::*, queue::{Queue, Node, WaitState, WakeList}};
+#[cfg(feature = "sync")]
+use crate::waker::Parker;
+
+/// A condition variable, that lets tasks and threads wait for an event while releasing a [```Mutex```](crate::Mutex)
+pub struct Condvar {
+    pub(crate) queue: Queue
+}
+
+impl Condvar {
+    loom_const_fn! {
+        /// Creates a new condition variable
+        #[inline(always)]
+        pub fn new () -> Self {
+            Self { queue: Queue::new() }
+        }
+    }
+
+    /// Returns a future that releases the guard's mutex, waits until the condvar is notified, and locks it again.
+    ///
+    /// The mutex isn't released until the future is first polled, and the waiter is queued before it is,
+    /// so a notification sent while holding the mutex can't be missed.
+    #[inline(always)]
+    pub fn wait<G: CondvarGuard> (&self, guard: G) -> CondvarFuture<'_, G> {
+        CondvarFuture {
+            condvar: self,
+            guard: Some(guard),
+            mutex: None,
+            node: Node::new(),
+            notified: false
+        }
+    }
+
+    /// Returns a future that waits on the condvar for as long as ```condition``` returns ```true```
+    #[inline(always)]
+    pub fn wait_while<G: CondvarGuard + DerefMut, F: FnMut(&mut G::Target) -> bool> (&self, guard: G, condition: F) -> CondvarWaitWhileFuture<'_, G, F> {
+        CondvarWaitWhileFuture {
+            condvar: self,
+            guard: Some(guard),
+            wait: None,
+            condition
+        }
+    }
+
+    /// Releases the guard's mutex, blocks the current thread until the condvar is notified, and locks it again
+    #[cfg(feature = "sync")]
+    #[inline]
+    pub fn wait_blocking<G: CondvarGuard> (&self, guard: G) -> G {
+        let parker = Parker::current();
+        let node = core::pin::pin!(Node::new());
+        self.queue.register_sync(node.as_ref(), &parker);
+
+        let mutex = guard.into_raw();
+        unsafe { G::mutex(&mutex).unlock_raw() }
+
+        loop {
+            parker.park();
+            if self.queue.register_sync(node.as_ref(), &parker) { break }
+        }
+
+        G::mutex(&mutex).lock_blocking_raw();
+        unsafe { G::from_raw(mutex) }
+    }
+
+    /// Wakes up one of the waiters, if there are any
+    #[inline(always)]
+    pub fn notify_one (&self) {
+        let mut notified = false;
+        self.notify(|| !core::mem::replace(&mut notified, true))
+    }
+
+    /// Wakes up every waiter
+    #[inline(always)]
+    pub fn notify_all (&self) {
+        self.notify(|| true)
+    }
+
+    #[inline]
+    fn notify<F: FnMut() -> bool> (&self, mut f: F) {
+        if self.queue.is_empty() { return }
+
+        loop {
+            let mut wakers = WakeList::new();
+            let more = self.queue.with(|queue| queue.handoff_while(&mut wakers, |_| f()));
+            wakers.wake();
+            if !more { break }
+        }
+    }
+
+    /// Removes a pending waiter from the queue, passing it's notification on if it had already been notified
+    #[inline]
+    pub(crate) fn cancel (&self, node: Pin<&Node>) {
+        if self.queue.remove(node) == Some(WaitState::HandedOff) {
+            self.notify_one()
+        }
+    }
+}
+
+impl Default for Condvar {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Condvar {
+    #[inline(always)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Condvar").finish_non_exhaustive()
+    }
+}
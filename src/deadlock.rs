@@ -0,0 +1,289 @@
+cfg_if::cfg_if! {
+    if #[cfg(feature = "deadlock-detection")] {
+        extern crate std;
+        extern crate alloc;
+
+        use core::{sync::atomic::{AtomicUsize, Ordering}, fmt::{Debug, Display}};
+        use alloc::{vec::Vec, collections::{BTreeMap, BTreeSet}};
+        use std::sync::{Mutex, PoisonError};
+
+        static NEXT_ID : AtomicUsize = AtomicUsize::new(1);
+        static GRAPH : Mutex<Graph> = Mutex::new(Graph::new());
+        static HANDLER : Mutex<fn(&LockOrderViolation)> = Mutex::new(default_handler);
+
+        std::thread_local! {
+            static THREAD : usize = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        }
+
+        /// Identity of a mutex in the lock-order graph, as returned by [```Mutex::lock_id```](crate::Mutex::lock_id)
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct LockId (usize);
+
+        /// Reported when a mutex is about to be locked in an order that contradicts one seen before,
+        /// which could deadlock if both orders happen at the same time
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct LockOrderViolation {
+            cycle: Vec<LockId>,
+            /// Names of the locks in ```cycle```, for the ones that have one
+            names: Vec<Option<&'static str>>
+        }
+
+        /// Sets the function that's called whenever a [```LockOrderViolation```] is found.
+        /// By default, it panics.
+        ///
+        /// Locks are tracked per thread, and per task for the ones locked with ```lock().await``` (and it's variants),
+        /// where the task is told apart by it's waker. Locks that a task takes without a waker, like with ```try_lock```
+        /// or by relocking through [```MutexGuard::unlocked_blocking```](crate::guards::MutexGuard::unlocked_blocking), are tracked as the thread's instead,
+        /// so an order inversion between them and the task's other locks isn't reported.
+        #[inline]
+        pub fn set_lock_order_handler (f: fn(&LockOrderViolation)) {
+            *HANDLER.lock().unwrap_or_else(PoisonError::into_inner) = f
+        }
+
+        /// Whoever holds or waits for a lock: either a thread, or a task (identified by it's waker)
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+        pub(crate) enum Holder {
+            Thread (usize),
+            Task (usize)
+        }
+
+        /// A mutex's node in the lock-order graph, which is only added once the mutex is first locked
+        pub(crate) struct Tracker {
+            id: AtomicUsize
+        }
+
+        /// Global lock-order graph
+        struct Graph {
+            /// Locks currently held by each holder, in the order they were acquired
+            held: BTreeMap<Holder, Vec<usize>>,
+            /// Current holder of each lock
+            owners: BTreeMap<usize, Holder>,
+            /// ```a -> b``` means that ```b``` has been locked while holding ```a```
+            edges: BTreeMap<usize, BTreeSet<usize>>,
+            /// Names of the locks that have one
+            names: BTreeMap<usize, &'static str>
+        }
+
+        impl LockOrderViolation {
+            /// Returns the locks involved, in the order they're locked in.
+            /// The first lock is the one being held, and the last one is the same as the first.
+            #[inline(always)]
+            pub fn cycle (&self) -> &[LockId] {
+                &self.cycle
+            }
+        }
+
+        impl Holder {
+            #[inline(always)]
+            pub fn thread () -> Self {
+                Self::Thread(THREAD.with(|x| *x))
+            }
+
+            #[inline(always)]
+            pub fn task (waker: &core::task::Waker) -> Self {
+                Self::Task(waker.data() as usize)
+            }
+        }
+
+        impl Tracker {
+            #[inline(always)]
+            pub const fn new () -> Self {
+                Self { id: AtomicUsize::new(0) }
+            }
+
+            #[inline]
+            pub fn id (&self) -> LockId {
+                let id = self.id.load(Ordering::Relaxed);
+                if id != 0 { return LockId(id) }
+
+                let new = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+                match self.id.compare_exchange(0, new, Ordering::Relaxed, Ordering::Relaxed) {
+                    Ok(_) => LockId(new),
+                    Err(id) => LockId(id)
+                }
+            }
+
+            /// ```holder``` is about to wait for the lock, so every lock it holds must be locked before this one
+            #[inline]
+            pub fn acquiring (&self, holder: Holder, name: Option<&'static str>) {
+                let id = self.id().0;
+                let violation = {
+                    let mut graph = graph();
+                    if let Some(name) = name {
+                        graph.names.insert(id, name);
+                    }
+                    graph.add_edges(holder, id)
+                };
+
+                // the graph's lock is released by now, so the handler may panic
+                if let Some(violation) = violation {
+                    let handler = *HANDLER.lock().unwrap_or_else(PoisonError::into_inner);
+                    handler(&violation)
+                }
+            }
+
+            #[inline]
+            pub fn acquired (&self, holder: Holder, name: Option<&'static str>) {
+                let id = self.id().0;
+                let mut graph = graph();
+                if let Some(name) = name {
+                    graph.names.insert(id, name);
+                }
+                graph.owners.insert(id, holder);
+                graph.held.entry(holder).or_default().push(id);
+            }
+
+            #[inline]
+            pub fn released (&self) {
+                let id = self.id.load(Ordering::Relaxed);
+                if id == 0 { return }
+
+                let mut graph = graph();
+                // ownership may have been handed to a waiter that gave it up before finding out
+                let Some(holder) = graph.owners.remove(&id) else { return };
+                if let Some(held) = graph.held.get_mut(&holder) {
+                    held.retain(|x| *x != id);
+                    if held.is_empty() { graph.held.remove(&holder); }
+                }
+            }
+        }
+
+        impl Graph {
+            #[inline(always)]
+            const fn new () -> Self {
+                Self { held: BTreeMap::new(), owners: BTreeMap::new(), edges: BTreeMap::new(), names: BTreeMap::new() }
+            }
+
+            /// Adds an edge from every lock held by ```holder``` to ```id```, returning the first cycle it closes
+            fn add_edges (&mut self, holder: Holder, id: usize) -> Option<LockOrderViolation> {
+                let held = self.held.get(&holder)?.clone();
+                let mut violation = None;
+
+                for prev in held {
+                    if prev == id || !self.edges.entry(prev).or_default().insert(id) { continue }
+                    if violation.is_some() { continue }
+
+                    // a path back from the new lock to a held one means they've been locked in the opposite order before
+                    if let Some(path) = self.path(id, prev) {
+                        let mut cycle = Vec::with_capacity(path.len() + 1);
+                        cycle.push(LockId(prev));
+                        cycle.extend(path.into_iter().map(LockId));
+                        let names = cycle.iter().map(|id| self.names.get(&id.0).copied()).collect();
+                        violation = Some(LockOrderViolation { cycle, names });
+                    }
+                }
+
+                violation
+            }
+
+            /// Depth-first search for a path from ```from``` to ```to```, including both ends
+            fn path (&self, from: usize, to: usize) -> Option<Vec<usize>> {
+                let mut visited = BTreeSet::new();
+                let mut stack = alloc::vec![(from, Vec::from([from]))];
+
+                while let Some((node, path)) = stack.pop() {
+                    if node == to { return Some(path) }
+                    if !visited.insert(node) { continue }
+
+                    for next in self.edges.get(&node).into_iter().flatten() {
+                        let mut path = path.clone();
+                        path.push(*next);
+                        stack.push((*next, path));
+                    }
+                }
+
+                None
+            }
+
+            /// Forgets a lock that's being dropped, so it's id never shows up again
+            fn remove (&mut self, id: usize) {
+                self.names.remove(&id);
+                self.edges.remove(&id);
+                for edges in self.edges.values_mut() {
+                    edges.remove(&id);
+                }
+
+                if let Some(holder) = self.owners.remove(&id) {
+                    if let Some(held) = self.held.get_mut(&holder) {
+                        held.retain(|x| *x != id)
+                    }
+                }
+            }
+        }
+
+        impl Drop for Tracker {
+            #[inline]
+            fn drop(&mut self) {
+                let id = *self.id.get_mut();
+                if id != 0 {
+                    graph().remove(id)
+                }
+            }
+        }
+
+        impl Display for LockId {
+            #[inline(always)]
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "#{}", self.0)
+            }
+        }
+
+        impl Display for LockOrderViolation {
+            #[inline]
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str("lock order violation: ")?;
+                for (i, (id, name)) in self.cycle.iter().zip(&self.names).enumerate() {
+                    if i > 0 { f.write_str(" -> ")? }
+                    match name {
+                        Some(name) => write!(f, "mutex '{name}' ({id})")?,
+                        None => write!(f, "mutex {id}")?
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        impl std::error::Error for LockOrderViolation {}
+
+        #[inline(always)]
+        fn graph () -> std::sync::MutexGuard<'static, Graph> {
+            GRAPH.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        fn default_handler (violation: &LockOrderViolation) {
+            panic!("{violation}")
+        }
+    } else {
+        #[derive(Clone, Copy)]
+        pub(crate) struct Holder;
+        pub(crate) struct Tracker;
+
+        impl Holder {
+            #[inline(always)]
+            pub fn thread () -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn task (_: &core::task::Waker) -> Self {
+                Self
+            }
+        }
+
+        impl Tracker {
+            #[inline(always)]
+            pub const fn new () -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn acquiring (&self, _: Holder, _: Option<&'static str>) {}
+
+            #[inline(always)]
+            pub fn acquired (&self, _: Holder, _: Option<&'static str>) {}
+
+            #[inline(always)]
+            pub fn released (&self) {}
+        }
+    }
+}
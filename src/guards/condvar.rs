@@ -0,0 +1,178 @@
+use core::{ops::DerefMut, task::Poll, mem::ManuallyDrop, pin::Pin};
+use futures::{Future, future::FusedFuture};
+use crate::{Condvar, Mutex, MutexPtr, movable::MovableMutex, queue::Node};
+use super::{MutexGuard, PtrMutexGuard};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A mutex guard that can be waited on with a [```Condvar```]
+pub trait CondvarGuard: Sized + sealed::Sealed {
+    #[doc(hidden)]
+    type Raw;
+
+    #[doc(hidden)]
+    fn into_raw (self) -> Self::Raw;
+
+    /// # Safety
+    /// The mutex must be locked by the caller
+    #[doc(hidden)]
+    unsafe fn from_raw (raw: Self::Raw) -> Self;
+
+    #[doc(hidden)]
+    fn mutex (raw: &Self::Raw) -> &MovableMutex;
+}
+
+impl<'a, T: ?Sized> sealed::Sealed for MutexGuard<'a, T> {}
+impl<'a, T: ?Sized> CondvarGuard for MutexGuard<'a, T> {
+    type Raw = &'a Mutex<T>;
+
+    #[inline(always)]
+    fn into_raw (self) -> Self::Raw {
+        let inner = self.inner;
+        core::mem::forget(self);
+        inner
+    }
+
+    #[inline(always)]
+    unsafe fn from_raw (raw: Self::Raw) -> Self {
+        Self::new(raw)
+    }
+
+    #[inline(always)]
+    fn mutex (raw: &Self::Raw) -> &MovableMutex {
+        &raw.inner
+    }
+}
+
+impl<P: MutexPtr> sealed::Sealed for PtrMutexGuard<P> {}
+impl<P: MutexPtr> CondvarGuard for PtrMutexGuard<P> {
+    type Raw = P;
+
+    #[inline(always)]
+    fn into_raw (self) -> Self::Raw {
+        let this = ManuallyDrop::new(self);
+        unsafe { core::ptr::read(&this.inner) }
+    }
+
+    #[inline(always)]
+    unsafe fn from_raw (raw: Self::Raw) -> Self {
+        Self::new(raw)
+    }
+
+    #[inline(always)]
+    fn mutex (raw: &Self::Raw) -> &MovableMutex {
+        &raw.inner
+    }
+}
+
+/// Future of [```wait```](Condvar::wait)
+pub struct CondvarFuture<'a, G: CondvarGuard> {
+    pub(crate) condvar: &'a Condvar,
+    /// Held until the waiter has been queued
+    pub(crate) guard: Option<G>,
+    /// The released mutex, once the waiter has been queued
+    pub(crate) mutex: Option<G::Raw>,
+    /// Slot on the condvar's queue, or on the mutex's once notified
+    pub(crate) node: Node,
+    pub(crate) notified: bool
+}
+
+impl<'a, G: CondvarGuard> Future for CondvarFuture<'a, G> {
+    type Output = G;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        // ```node``` is structurally pinned: it's linked into the condvar's queue (and then the mutex's) by it's address,
+        // so it's never moved out of ```this```, and it's taken off whichever queue it's on before it's dropped
+        let this = unsafe { self.get_unchecked_mut() };
+
+        if let Some(guard) = this.guard.take() {
+            // queued before the mutex is released, so nobody can notify us in between
+            this.condvar.queue.register(unsafe { Pin::new_unchecked(&this.node) }, cx.waker());
+            let mutex = this.mutex.insert(guard.into_raw());
+            unsafe { G::mutex(mutex).unlock_raw() }
+            return Poll::Pending;
+        }
+
+        let mutex = if let Some(ref mutex) = this.mutex { mutex } else { panic!("Condvar future already consumed") };
+        if !this.notified {
+            if !this.condvar.queue.register(unsafe { Pin::new_unchecked(&this.node) }, cx.waker()) {
+                return Poll::Pending;
+            }
+            this.notified = true;
+        }
+
+        if G::mutex(mutex).poll_lock(unsafe { Pin::new_unchecked(&this.node) }, cx).is_ready() {
+            let mutex = this.mutex.take().unwrap();
+            return Poll::Ready(unsafe { G::from_raw(mutex) });
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<'a, G: CondvarGuard> FusedFuture for CondvarFuture<'a, G> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.guard.is_none() && self.mutex.is_none()
+    }
+}
+
+impl<'a, G: CondvarGuard> Drop for CondvarFuture<'a, G> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(ref mutex) = self.mutex {
+            match self.notified {
+                true => G::mutex(mutex).cancel(unsafe { Pin::new_unchecked(&self.node) }),
+                false => self.condvar.cancel(unsafe { Pin::new_unchecked(&self.node) })
+            }
+        }
+    }
+}
+
+/// Future of [```wait_while```](Condvar::wait_while)
+pub struct CondvarWaitWhileFuture<'a, G: CondvarGuard, F> {
+    pub(crate) condvar: &'a Condvar,
+    pub(crate) guard: Option<G>,
+    pub(crate) wait: Option<CondvarFuture<'a, G>>,
+    pub(crate) condition: F
+}
+
+impl<'a, G: CondvarGuard + DerefMut, F: FnMut(&mut G::Target) -> bool> Future for CondvarWaitWhileFuture<'a, G, F> {
+    type Output = G;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        // ```wait``` is structurally pinned, since it's node is linked into the condvar's and mutex's queues by address.
+        // It's only ever dropped in place, and a new future is only put in the slot once the old one is gone.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        loop {
+            if let Some(ref mut wait) = this.wait {
+                match unsafe { Pin::new_unchecked(wait) }.poll(cx) {
+                    Poll::Ready(guard) => {
+                        this.wait = None;
+                        this.guard = Some(guard);
+                    },
+                    Poll::Pending => return Poll::Pending
+                }
+            }
+
+            let mut guard = this.guard.take().expect("Condvar future already consumed");
+            if !(this.condition)(&mut guard) {
+                return Poll::Ready(guard);
+            }
+
+            this.wait = Some(this.condvar.wait(guard));
+        }
+    }
+}
+
+impl<'a, G: CondvarGuard + DerefMut, F: FnMut(&mut G::Target) -> bool> FusedFuture for CondvarWaitWhileFuture<'a, G, F> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.guard.is_none() && self.wait.is_none()
+    }
+}
@@ -0,0 +1,62 @@
+use core::{task::Poll, pin::Pin};
+use futures::{Future, future::FusedFuture};
+use crate::{LockAll, queue::Node};
+
+/// Future of [```lock_all```](crate::lock_all)
+pub struct LockAllFuture<L: LockAll> {
+    pub(crate) mutexes: Option<L>,
+    /// Mutexes to lock, sorted by address
+    pub(crate) order: L::Order,
+    /// Number of mutexes in ```order``` that have already been locked
+    pub(crate) locked: usize,
+    pub(crate) node: Node
+}
+
+impl<L: LockAll> Future for LockAllFuture<L> {
+    type Output = L::Guards;
+
+    #[inline]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.mutexes.is_none() { panic!("Mutex future already consumed") }
+
+        let order = this.order.as_mut();
+        while let Some(mutex) = order.get(this.locked) {
+            // the node is off the queue once a mutex is locked, so it's reused for the next one
+            if unsafe { &**mutex }.poll_lock(unsafe { Pin::new_unchecked(&this.node) }, cx).is_pending() {
+                return Poll::Pending;
+            }
+
+            this.locked += 1;
+        }
+
+        let mutexes = this.mutexes.take().unwrap();
+        Poll::Ready(unsafe { mutexes.into_guards() })
+    }
+}
+
+impl<L: LockAll> FusedFuture for LockAllFuture<L> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.mutexes.is_none()
+    }
+}
+
+impl<L: LockAll> Drop for LockAllFuture<L> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.mutexes.is_none() { return }
+
+        let order = self.order.as_mut();
+        if let Some(mutex) = order.get(self.locked) {
+            unsafe { &**mutex }.cancel(unsafe { Pin::new_unchecked(&self.node) })
+        }
+
+        for mutex in order[..self.locked].iter().rev() {
+            unsafe { (**mutex).unlock_raw() }
+        }
+    }
+}
+
+unsafe impl<L: LockAll + Send> Send for LockAllFuture<L> {}
+unsafe impl<L: LockAll + Sync> Sync for LockAllFuture<L> {}
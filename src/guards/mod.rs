@@ -1,15 +1,103 @@
-use core::{ops::{Deref, DerefMut}, task::Poll};
+use core::{ops::{Deref, DerefMut}, task::Poll, marker::PhantomData, pin::Pin};
 use futures::{Future, future::FusedFuture};
-use crate::{Mutex};
+use crate::{Mutex, movable::MovableMutex, poison, queue::Node};
 
-#[repr(transparent)]
+#[must_use = "the mutex is unlocked as soon as the guard is dropped"]
 pub struct MutexGuard<'a, T: ?Sized> {
-    pub(crate) inner: &'a Mutex<T>
+    pub(crate) inner: &'a Mutex<T>,
+    pub(crate) poison: poison::Guard
 }
 
 impl<'a, T: ?Sized> MutexGuard<'a, T> {
+    #[inline(always)]
+    pub(crate) fn new (inner: &'a Mutex<T>) -> Self {
+        Self { inner, poison: inner.poison.guard() }
+    }
+
     #[inline(always)]
     pub fn unlock (self) {}
+
+    /// Unlocks the mutex, handing it straight to the first waiter (if any) regardless of the mutex's fairness policy
+    #[inline(always)]
+    pub fn unlock_fair (self) {
+        let inner = self.inner;
+        inner.poison.done(&self.poison);
+        core::mem::forget(self);
+        unsafe { inner.inner.unlock_fair_raw() }
+    }
+
+    /// Returns ```true``` if anyone is waiting for the mutex
+    #[inline(always)]
+    pub fn has_waiters (&self) -> bool {
+        self.inner.inner.has_waiters()
+    }
+
+    /// Returns ```true``` if a thread panicked while holding the lock
+    #[cfg(feature = "poison")]
+    #[inline(always)]
+    pub fn is_poisoned (&self) -> bool {
+        self.inner.poison.get()
+    }
+
+    /// Unlocks the mutex while ```f``` runs, blocking the current thread until it's locked again before returning ```f```'s result.
+    /// The guard carries on even if the mutex was poisoned in the meantime.
+    ///
+    /// The thread is parked while it waits, so it mustn't be called from an async task: use [```unlocked_async```](MutexGuard::unlocked_async) there.
+    /// Otherwise the executor's thread is blocked, and a single-threaded runtime deadlocks if the mutex's holder needs that thread to make progress.
+    ///
+    /// # Panics
+    /// If ```f``` panics, the mutex is locked again (blocking the thread) before the panic carries on.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn unlocked_blocking<R, F: FnOnce() -> R> (this: &mut Self, f: F) -> R {
+        this.inner.poison.done(&this.poison);
+        let result = unsafe { this.inner.inner.unlocked_blocking_raw(f) };
+        this.poison = this.inner.poison.guard();
+        result
+    }
+
+    /// Unlocks the mutex while ```f``` runs, then waits to lock it again, resolving to the new guard and ```f```'s output.
+    /// If it's dropped part way, the mutex stays unlocked.
+    #[inline]
+    pub async fn unlocked_async<F: Future> (self, f: F) -> (Self, F::Output) {
+        let mutex = self.inner;
+        drop(self);
+        let output = f.await;
+        (mutex.lock().await, output)
+    }
+
+    /// If anyone is waiting for the mutex, hands it straight to the first waiter and waits to lock it again.
+    /// Otherwise, the guard is given back right away.
+    #[inline]
+    pub async fn bump (self) -> Self {
+        if !self.has_waiters() {
+            return self
+        }
+
+        let mutex = self.inner;
+        self.unlock_fair();
+        mutex.lock().await
+    }
+
+    /// Makes a [```MappedMutexGuard```] for a component of the locked data
+    #[inline]
+    pub fn map<U: ?Sized, F: FnOnce(&mut T) -> &mut U> (this: Self, f: F) -> MappedMutexGuard<'a, U> {
+        let data = f(this.inner.data.with_mut(|data| unsafe { &mut *data })) as *mut U;
+        let MutexGuard { inner, poison } = *core::mem::ManuallyDrop::new(this);
+        MappedMutexGuard { mutex: &inner.inner, flag: &inner.poison, poison, data, _phantom: PhantomData }
+    }
+
+    /// Attempts to make a [```MappedMutexGuard```] for a component of the locked data, giving back the original guard if ```f``` returns ```None```
+    #[inline]
+    pub fn try_map<U: ?Sized, F: FnOnce(&mut T) -> Option<&mut U>> (this: Self, f: F) -> Result<MappedMutexGuard<'a, U>, Self> {
+        let data = match f(this.inner.data.with_mut(|data| unsafe { &mut *data })) {
+            Some(data) => data as *mut U,
+            None => return Err(this)
+        };
+
+        let MutexGuard { inner, poison } = *core::mem::ManuallyDrop::new(this);
+        Ok(MappedMutexGuard { mutex: &inner.inner, flag: &inner.poison, poison, data, _phantom: PhantomData })
+    }
 }
 
 impl<'a, T: ?Sized> Deref for MutexGuard<'a, T> {
@@ -17,42 +105,117 @@ impl<'a, T: ?Sized> Deref for MutexGuard<'a, T> {
 
     #[inline(always)]
     fn deref(&self) -> &Self::Target {
-        unsafe { &*self.inner.data.get() }
+        self.inner.data.with(|data| unsafe { &*data })
     }
 }
 
 impl<'a, T: ?Sized> DerefMut for MutexGuard<'a, T> {
     #[inline(always)]
     fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { &mut *self.inner.data.get() }
+        self.inner.data.with_mut(|data| unsafe { &mut *data })
     }
 }
 
 impl<'a, T: ?Sized> Drop for MutexGuard<'a, T> {
     #[inline(always)]
     fn drop(&mut self) {
-        unsafe { self.inner.inner.unlock(); }
+        self.inner.poison.done(&self.poison);
+        unsafe { self.inner.inner.unlock_raw(); }
     }
 }
 
-/// Future that resolves to an owned mutex guard
-#[repr(transparent)]
+/// Mutex guard that only gives access to a component of the locked data, made with [```MutexGuard::map```]
+pub struct MappedMutexGuard<'a, U: ?Sized> {
+    pub(crate) mutex: &'a MovableMutex,
+    pub(crate) flag: &'a poison::Flag,
+    pub(crate) poison: poison::Guard,
+    pub(crate) data: *mut U,
+    pub(crate) _phantom: PhantomData<&'a mut U>
+}
+
+impl<'a, U: ?Sized> MappedMutexGuard<'a, U> {
+    #[inline(always)]
+    pub fn unlock (self) {}
+
+    /// Unlocks the mutex, handing it straight to the first waiter (if any) regardless of the mutex's fairness policy
+    #[inline(always)]
+    pub fn unlock_fair (self) {
+        let mutex = self.mutex;
+        self.flag.done(&self.poison);
+        core::mem::forget(self);
+        unsafe { mutex.unlock_fair_raw() }
+    }
+
+    /// Makes a [```MappedMutexGuard```] for a component of the already mapped data
+    #[inline]
+    pub fn map<V: ?Sized, F: FnOnce(&mut U) -> &mut V> (this: Self, f: F) -> MappedMutexGuard<'a, V> {
+        let data = f(unsafe { &mut *this.data }) as *mut V;
+        let MappedMutexGuard { mutex, flag, poison, .. } = *core::mem::ManuallyDrop::new(this);
+        MappedMutexGuard { mutex, flag, poison, data, _phantom: PhantomData }
+    }
+
+    /// Attempts to make a [```MappedMutexGuard```] for a component of the already mapped data, giving back the original guard if ```f``` returns ```None```
+    #[inline]
+    pub fn try_map<V: ?Sized, F: FnOnce(&mut U) -> Option<&mut V>> (this: Self, f: F) -> Result<MappedMutexGuard<'a, V>, Self> {
+        let data = match f(unsafe { &mut *this.data }) {
+            Some(data) => data as *mut V,
+            None => return Err(this)
+        };
+
+        let MappedMutexGuard { mutex, flag, poison, .. } = *core::mem::ManuallyDrop::new(this);
+        Ok(MappedMutexGuard { mutex, flag, poison, data, _phantom: PhantomData })
+    }
+}
+
+impl<'a, U: ?Sized> Deref for MappedMutexGuard<'a, U> {
+    type Target = U;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.data }
+    }
+}
+
+impl<'a, U: ?Sized> DerefMut for MappedMutexGuard<'a, U> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.data }
+    }
+}
+
+impl<'a, U: ?Sized> Drop for MappedMutexGuard<'a, U> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.flag.done(&self.poison);
+        unsafe { self.mutex.unlock_raw(); }
+    }
+}
+
+// a guard only gives access to the data, and the mutex is unlocked the same way from any thread
+unsafe impl<'a, T: ?Sized + Send> Send for MutexGuard<'a, T> {}
+unsafe impl<'a, T: ?Sized + Sync> Sync for MutexGuard<'a, T> {}
+
+unsafe impl<'a, U: ?Sized + Send> Send for MappedMutexGuard<'a, U> {}
+unsafe impl<'a, U: ?Sized + Sync> Sync for MappedMutexGuard<'a, U> {}
+
+/// Future that resolves to a mutex guard
 pub struct MutexFuture<'a, T: ?Sized> {
-    pub(crate) guard: Option<MutexGuard<'a, T>>
+    pub(crate) mutex: Option<&'a Mutex<T>>,
+    pub(crate) node: Node
 }
 
 impl<'a, T: ?Sized> Future for MutexFuture<'a, T> {
     type Output = MutexGuard<'a, T>;
 
     #[inline(always)]
-    fn poll(mut self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
-        let guard = if let Some(ref mut guard) = self.guard { guard } else { panic!("Mutex future already consumed") };
-        if guard.inner.inner.try_lock() {
-            let guard = core::mem::take(&mut self.guard).unwrap();
-            return Poll::Ready(guard);
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let mutex = if let Some(mutex) = this.mutex { mutex } else { panic!("Mutex future already consumed") };
+        if mutex.inner.poll_lock(unsafe { Pin::new_unchecked(&this.node) }, cx).is_ready() {
+            this.mutex = None;
+            return Poll::Ready(MutexGuard::new(mutex));
         }
 
-        guard.inner.inner.queue.push(cx.waker().clone().into());
         Poll::Pending
     }
 }
@@ -60,8 +223,19 @@ impl<'a, T: ?Sized> Future for MutexFuture<'a, T> {
 impl<'a, T: ?Sized> FusedFuture for MutexFuture<'a, T> {
     #[inline(always)]
     fn is_terminated(&self) -> bool {
-        self.guard.is_none()
+        self.mutex.is_none()
+    }
+}
+
+impl<'a, T: ?Sized> Drop for MutexFuture<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(mutex) = self.mutex {
+            mutex.inner.cancel(unsafe { Pin::new_unchecked(&self.node) })
+        }
     }
 }
 
-flat_mod!(owned, atomic);
\ No newline at end of file
+flat_mod!(rwlock, semaphore, condvar, lock_all, owned, timeout, movable, reentrant, waiter);
+#[cfg(feature = "poison")]
+flat_mod!(poisoned);
\ No newline at end of file
@@ -0,0 +1,100 @@
+use core::{task::Poll, pin::Pin};
+use futures::{Future, future::FusedFuture};
+use crate::{movable::MovableMutex, queue::Node};
+
+/// Lock token of a [```MovableMutex```], which unlocks it when dropped
+#[must_use = "the mutex is unlocked as soon as the token is dropped"]
+pub struct MovableMutexGuard<'a> {
+    pub(crate) mutex: &'a MovableMutex
+}
+
+impl<'a> MovableMutexGuard<'a> {
+    /// Makes a token for a mutex that was locked without one (or whose token was turned [```into_raw```](MovableMutexGuard::into_raw))
+    ///
+    /// # Safety
+    /// The mutex must be locked, and the caller must be the one that locked it
+    #[inline(always)]
+    pub unsafe fn from_raw (mutex: &'a MovableMutex) -> Self {
+        mutex.owner.claimed(mutex.name);
+        Self { mutex }
+    }
+
+    /// Returns the mutex the token belongs to
+    #[inline(always)]
+    pub fn mutex (&self) -> &'a MovableMutex {
+        self.mutex
+    }
+
+    /// Unlocks the mutex, the same as dropping the token
+    #[inline(always)]
+    pub fn unlock (self) {}
+
+    /// Unlocks the mutex, handing it straight to the first waiter (if any) regardless of the mutex's fairness policy
+    #[inline(always)]
+    pub fn unlock_fair (self) {
+        unsafe { self.into_raw().unlock_fair() }
+    }
+
+    /// Drops the token without unlocking the mutex, which stays locked until it's [```unlock```](MovableMutex::unlock)ed by hand
+    #[inline(always)]
+    pub fn forget (self) {
+        self.into_raw();
+    }
+
+    /// Drops the token without unlocking the mutex, returning the mutex
+    #[inline(always)]
+    pub fn into_raw (self) -> &'a MovableMutex {
+        let mutex = self.mutex;
+        mutex.owner.forgotten();
+        core::mem::forget(self);
+        mutex
+    }
+}
+
+impl<'a> Drop for MovableMutexGuard<'a> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { self.mutex.unlock_raw() }
+    }
+}
+
+/// Future of [```lock```](MovableMutex::lock)
+pub struct MovableMutexFuture<'a> {
+    pub(crate) mutex: Option<&'a MovableMutex>,
+    pub(crate) node: Node
+}
+
+impl<'a> Future for MovableMutexFuture<'a> {
+    type Output = MovableMutexGuard<'a>;
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let mutex = if let Some(mutex) = this.mutex { mutex } else { panic!("Mutex future already consumed") };
+        if mutex.poll_lock(unsafe { Pin::new_unchecked(&this.node) }, cx).is_ready() {
+            this.mutex = None;
+            return Poll::Ready(MovableMutexGuard { mutex });
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<'a> FusedFuture for MovableMutexFuture<'a> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.mutex.is_none()
+    }
+}
+
+impl<'a> Drop for MovableMutexFuture<'a> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(mutex) = self.mutex {
+            mutex.cancel(unsafe { Pin::new_unchecked(&self.node) })
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+flat_mod!(owned, atomic);
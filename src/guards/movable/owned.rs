@@ -0,0 +1,98 @@
+extern crate alloc;
+use core::{task::Poll, pin::Pin, mem::ManuallyDrop};
+use alloc::rc::Rc;
+use futures::{Future, future::FusedFuture};
+use crate::{movable::MovableMutex, queue::Node};
+
+/// Lock token of a [```MovableMutex```] that keeps it alive through an [```Rc```], and unlocks it when dropped
+#[must_use = "the mutex is unlocked as soon as the token is dropped"]
+pub struct OwnedMovableMutexGuard {
+    pub(crate) mutex: Rc<MovableMutex>
+}
+
+impl OwnedMovableMutexGuard {
+    /// Makes a token for a mutex that was locked without one (or whose token was turned [```into_raw```](OwnedMovableMutexGuard::into_raw))
+    ///
+    /// # Safety
+    /// The mutex must be locked, and the caller must be the one that locked it
+    #[inline(always)]
+    pub unsafe fn from_raw (mutex: Rc<MovableMutex>) -> Self {
+        mutex.owner.claimed(mutex.name);
+        Self { mutex }
+    }
+
+    /// Returns the mutex the token belongs to
+    #[inline(always)]
+    pub fn mutex (&self) -> &Rc<MovableMutex> {
+        &self.mutex
+    }
+
+    /// Unlocks the mutex, the same as dropping the token
+    #[inline(always)]
+    pub fn unlock (self) {}
+
+    /// Unlocks the mutex, handing it straight to the first waiter (if any) regardless of the mutex's fairness policy
+    #[inline(always)]
+    pub fn unlock_fair (self) {
+        unsafe { self.into_raw().unlock_fair() }
+    }
+
+    /// Drops the token without unlocking the mutex, which stays locked until it's [```unlock```](MovableMutex::unlock)ed by hand
+    #[inline(always)]
+    pub fn forget (self) {
+        drop(self.into_raw())
+    }
+
+    /// Drops the token without unlocking the mutex, returning the mutex
+    #[inline(always)]
+    pub fn into_raw (self) -> Rc<MovableMutex> {
+        let this = ManuallyDrop::new(self);
+        this.mutex.owner.forgotten();
+        unsafe { core::ptr::read(&this.mutex) }
+    }
+}
+
+impl Drop for OwnedMovableMutexGuard {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { self.mutex.unlock_raw() }
+    }
+}
+
+/// Future of [```lock_owned```](MovableMutex::lock_owned)
+pub struct OwnedMovableMutexFuture {
+    pub(crate) mutex: Option<Rc<MovableMutex>>,
+    pub(crate) node: Node
+}
+
+impl Future for OwnedMovableMutexFuture {
+    type Output = OwnedMovableMutexGuard;
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let mutex = if let Some(ref mutex) = this.mutex { mutex } else { panic!("Mutex future already consumed") };
+        if mutex.poll_lock(unsafe { Pin::new_unchecked(&this.node) }, cx).is_ready() {
+            let mutex = core::mem::take(&mut this.mutex).unwrap();
+            return Poll::Ready(OwnedMovableMutexGuard { mutex });
+        }
+
+        Poll::Pending
+    }
+}
+
+impl FusedFuture for OwnedMovableMutexFuture {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.mutex.is_none()
+    }
+}
+
+impl Drop for OwnedMovableMutexFuture {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(ref mutex) = self.mutex {
+            mutex.cancel(unsafe { Pin::new_unchecked(&self.node) })
+        }
+    }
+}
@@ -1,67 +1,273 @@
+#[cfg(feature = "alloc")]
 extern crate alloc;
-use core::{ops::{Deref, DerefMut}, task::Poll};
-use alloc::rc::Rc;
+
+use core::{ops::{Deref, DerefMut}, task::Poll, mem::ManuallyDrop, pin::Pin};
+#[cfg(feature = "alloc")]
+use alloc::{rc::Rc, sync::Arc};
 use futures::{future::FusedFuture, Future};
-use crate::{Mutex};
+use crate::{Mutex, MutexPtr, poison, queue::Node};
+
+/// Mutex guard that keeps the pointer the mutex was locked through, made with [```Mutex::lock_ptr```] and friends
+#[must_use = "the mutex is unlocked as soon as the guard is dropped"]
+pub struct PtrMutexGuard<P: MutexPtr> {
+    pub(crate) inner: P,
+    pub(crate) poison: poison::Guard
+}
+
+/// Owned mutex guard that keeps the mutex alive through an [```Rc```]
+#[cfg(feature = "alloc")]
+pub type OwnedMutexGuard<T> = PtrMutexGuard<Rc<Mutex<T>>>;
+/// Owned mutex guard that keeps the mutex alive through an [```Arc```]
+#[cfg(feature = "alloc")]
+pub type AtomicMutexGuard<T> = PtrMutexGuard<Arc<Mutex<T>>>;
+
+impl<P: MutexPtr> PtrMutexGuard<P> {
+    #[inline(always)]
+    pub(crate) fn new (inner: P) -> Self {
+        let poison = inner.poison.guard();
+        Self { inner, poison }
+    }
+
+    #[inline(always)]
+    pub fn unlock (self) {}
+
+    /// Unlocks the mutex, handing it straight to the first waiter (if any) regardless of the mutex's fairness policy
+    #[inline(always)]
+    pub fn unlock_fair (self) {
+        let this = ManuallyDrop::new(self);
+        let inner = unsafe { core::ptr::read(&this.inner) };
+        inner.poison.done(&this.poison);
+        unsafe { inner.inner.unlock_fair_raw() }
+    }
+
+    /// Returns ```true``` if anyone is waiting for the mutex
+    #[inline(always)]
+    pub fn has_waiters (&self) -> bool {
+        self.inner.inner.has_waiters()
+    }
+
+    /// Returns ```true``` if a thread panicked while holding the lock
+    #[cfg(feature = "poison")]
+    #[inline(always)]
+    pub fn is_poisoned (&self) -> bool {
+        self.inner.poison.get()
+    }
+
+    /// Unlocks the mutex while ```f``` runs, blocking the current thread until it's locked again before returning ```f```'s result.
+    /// The guard carries on even if the mutex was poisoned in the meantime.
+    ///
+    /// The thread is parked while it waits, so it mustn't be called from an async task: use [```unlocked_async```](PtrMutexGuard::unlocked_async) there.
+    /// Otherwise the executor's thread is blocked, and a single-threaded runtime deadlocks if the mutex's holder needs that thread to make progress.
+    ///
+    /// # Panics
+    /// If ```f``` panics, the mutex is locked again (blocking the thread) before the panic carries on.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn unlocked_blocking<R, F: FnOnce() -> R> (this: &mut Self, f: F) -> R {
+        this.inner.poison.done(&this.poison);
+        let result = unsafe { this.inner.inner.unlocked_blocking_raw(f) };
+        this.poison = this.inner.poison.guard();
+        result
+    }
+
+    /// Unlocks the mutex while ```f``` runs, then waits to lock it again through the same pointer, resolving to the new guard and ```f```'s output.
+    /// If it's dropped part way, the mutex stays unlocked.
+    #[inline]
+    pub async fn unlocked_async<F: Future> (self, f: F) -> (Self, F::Output) {
+        let inner = self.into_ptr(false);
+        let output = f.await;
+        (Mutex::lock_ptr(inner).await, output)
+    }
+
+    /// If anyone is waiting for the mutex, hands it straight to the first waiter and waits to lock it again through the same pointer.
+    /// Otherwise, the guard is given back right away.
+    #[inline]
+    pub async fn bump (self) -> Self {
+        if !self.has_waiters() {
+            return self
+        }
+
+        Mutex::lock_ptr(self.into_ptr(true)).await
+    }
+
+    /// Unlocks the mutex (handing it straight to the first waiter if ```fair```), giving back the pointer it was locked through
+    #[inline(always)]
+    fn into_ptr (self, fair: bool) -> P {
+        let this = ManuallyDrop::new(self);
+        let inner = unsafe { core::ptr::read(&this.inner) };
+        inner.poison.done(&this.poison);
+        match fair {
+            true => unsafe { inner.inner.unlock_fair_raw() },
+            false => unsafe { inner.inner.unlock_raw() }
+        }
+
+        inner
+    }
+
+    /// Makes a [```PtrMappedMutexGuard```] for a component of the locked data, which keeps the pointer
+    #[inline]
+    pub fn map<U: ?Sized, F: FnOnce(&mut P::Data) -> &mut U> (this: Self, f: F) -> PtrMappedMutexGuard<P, U> {
+        let data = f(this.inner.data.with_mut(|data| unsafe { &mut *data })) as *mut U;
+        let this = ManuallyDrop::new(this);
+        PtrMappedMutexGuard { inner: unsafe { core::ptr::read(&this.inner) }, poison: this.poison, data }
+    }
+
+    /// Attempts to make a [```PtrMappedMutexGuard```] for a component of the locked data, giving back the original guard if ```f``` returns ```None```
+    #[inline]
+    pub fn try_map<U: ?Sized, F: FnOnce(&mut P::Data) -> Option<&mut U>> (this: Self, f: F) -> Result<PtrMappedMutexGuard<P, U>, Self> {
+        let data = match f(this.inner.data.with_mut(|data| unsafe { &mut *data })) {
+            Some(data) => data as *mut U,
+            None => return Err(this)
+        };
+
+        let this = ManuallyDrop::new(this);
+        Ok(PtrMappedMutexGuard { inner: unsafe { core::ptr::read(&this.inner) }, poison: this.poison, data })
+    }
+}
+
+impl<P: MutexPtr> Deref for PtrMutexGuard<P> {
+    type Target = P::Data;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.inner.data.with(|data| unsafe { &*data })
+    }
+}
+
+impl<P: MutexPtr> DerefMut for PtrMutexGuard<P> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.inner.data.with_mut(|data| unsafe { &mut *data })
+    }
+}
+
+impl<P: MutexPtr> Drop for PtrMutexGuard<P> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.inner.poison.done(&self.poison);
+        unsafe { self.inner.inner.unlock_raw(); }
+    }
+}
 
-#[repr(transparent)]
-pub struct OwnedMutexGuard<T: ?Sized> {
-    pub(crate) inner: Rc<Mutex<T>>
+/// Mutex guard that only gives access to a component of the locked data, made with [```PtrMutexGuard::map```]
+pub struct PtrMappedMutexGuard<P: MutexPtr, U: ?Sized> {
+    pub(crate) inner: P,
+    pub(crate) poison: poison::Guard,
+    pub(crate) data: *mut U
 }
 
-impl<T: ?Sized> OwnedMutexGuard<T> {
+/// Mapped mutex guard that keeps the mutex alive through an [```Rc```]
+#[cfg(feature = "alloc")]
+pub type OwnedMappedMutexGuard<T, U> = PtrMappedMutexGuard<Rc<Mutex<T>>, U>;
+/// Mapped mutex guard that keeps the mutex alive through an [```Arc```]
+#[cfg(feature = "alloc")]
+pub type AtomicMappedMutexGuard<T, U> = PtrMappedMutexGuard<Arc<Mutex<T>>, U>;
+
+impl<P: MutexPtr, U: ?Sized> PtrMappedMutexGuard<P, U> {
     #[inline(always)]
     pub fn unlock (self) {}
+
+    /// Unlocks the mutex, handing it straight to the first waiter (if any) regardless of the mutex's fairness policy
+    #[inline(always)]
+    pub fn unlock_fair (self) {
+        let this = ManuallyDrop::new(self);
+        let inner = unsafe { core::ptr::read(&this.inner) };
+        inner.poison.done(&this.poison);
+        unsafe { inner.inner.unlock_fair_raw() }
+    }
+
+    /// Makes a [```PtrMappedMutexGuard```] for a component of the already mapped data
+    #[inline]
+    pub fn map<V: ?Sized, F: FnOnce(&mut U) -> &mut V> (this: Self, f: F) -> PtrMappedMutexGuard<P, V> {
+        let data = f(unsafe { &mut *this.data }) as *mut V;
+        let this = ManuallyDrop::new(this);
+        PtrMappedMutexGuard { inner: unsafe { core::ptr::read(&this.inner) }, poison: this.poison, data }
+    }
+
+    /// Attempts to make a [```PtrMappedMutexGuard```] for a component of the already mapped data, giving back the original guard if ```f``` returns ```None```
+    #[inline]
+    pub fn try_map<V: ?Sized, F: FnOnce(&mut U) -> Option<&mut V>> (this: Self, f: F) -> Result<PtrMappedMutexGuard<P, V>, Self> {
+        let data = match f(unsafe { &mut *this.data }) {
+            Some(data) => data as *mut V,
+            None => return Err(this)
+        };
+
+        let this = ManuallyDrop::new(this);
+        Ok(PtrMappedMutexGuard { inner: unsafe { core::ptr::read(&this.inner) }, poison: this.poison, data })
+    }
 }
 
-impl<T: ?Sized> Deref for OwnedMutexGuard<T> {
-    type Target = T;
+impl<P: MutexPtr, U: ?Sized> Deref for PtrMappedMutexGuard<P, U> {
+    type Target = U;
 
     #[inline(always)]
     fn deref(&self) -> &Self::Target {
-        unsafe { &*self.inner.data.get() }
+        unsafe { &*self.data }
     }
 }
 
-impl<T: ?Sized> DerefMut for OwnedMutexGuard<T> {
+impl<P: MutexPtr, U: ?Sized> DerefMut for PtrMappedMutexGuard<P, U> {
     #[inline(always)]
     fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { &mut *self.inner.data.get() }
+        unsafe { &mut *self.data }
     }
 }
 
-impl<T: ?Sized> Drop for OwnedMutexGuard<T> {
+impl<P: MutexPtr, U: ?Sized> Drop for PtrMappedMutexGuard<P, U> {
     #[inline(always)]
     fn drop(&mut self) {
-        unsafe { self.inner.inner.unlock(); }
+        self.inner.poison.done(&self.poison);
+        unsafe { self.inner.inner.unlock_raw(); }
     }
 }
 
-/// Future that resolves to an owned mutex guard
-#[repr(transparent)]
-pub struct OwnedMutexFuture<T: ?Sized> {
-    pub(crate) guard: Option<OwnedMutexGuard<T>>
+/// Future that resolves to a [```PtrMutexGuard```]
+pub struct PtrMutexFuture<P: MutexPtr> {
+    pub(crate) mutex: Option<P>,
+    pub(crate) node: Node
 }
 
-impl<T: ?Sized> Future for OwnedMutexFuture<T> {
-    type Output = OwnedMutexGuard<T>;
+/// Future that resolves to an [```OwnedMutexGuard```]
+#[cfg(feature = "alloc")]
+pub type OwnedMutexFuture<T> = PtrMutexFuture<Rc<Mutex<T>>>;
+/// Future that resolves to an [```AtomicMutexGuard```]
+#[cfg(feature = "alloc")]
+pub type AtomicMutexFuture<T> = PtrMutexFuture<Arc<Mutex<T>>>;
+
+impl<P: MutexPtr> Future for PtrMutexFuture<P> {
+    type Output = PtrMutexGuard<P>;
 
     #[inline(always)]
-    fn poll(mut self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
-        let guard = if let Some(ref mut guard) = self.guard { guard } else { panic!("Mutex future already consumed") };
-        if guard.inner.inner.try_lock() {
-            let guard = core::mem::take(&mut self.guard).unwrap();
-            return Poll::Ready(guard);
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let mutex = if let Some(ref mutex) = this.mutex { mutex } else { panic!("Mutex future already consumed") };
+        if mutex.inner.poll_lock(unsafe { Pin::new_unchecked(&this.node) }, cx).is_ready() {
+            let mutex = core::mem::take(&mut this.mutex).unwrap();
+            return Poll::Ready(PtrMutexGuard::new(mutex));
         }
 
-        guard.inner.inner.queue.push(cx.waker().clone().into());
         Poll::Pending
     }
 }
 
-impl<'a, T: ?Sized> FusedFuture for OwnedMutexFuture<T> {
+impl<P: MutexPtr> FusedFuture for PtrMutexFuture<P> {
     #[inline(always)]
     fn is_terminated(&self) -> bool {
-        self.guard.is_none()
+        self.mutex.is_none()
     }
-}
\ No newline at end of file
+}
+
+impl<P: MutexPtr> Drop for PtrMutexFuture<P> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(ref mutex) = self.mutex {
+            mutex.inner.cancel(unsafe { Pin::new_unchecked(&self.node) })
+        }
+    }
+}
+
+// the guard may be the one to drop the pointer, and with it the data
+unsafe impl<P: MutexPtr + Send> Send for PtrMutexGuard<P> where P::Data: Send {}
+unsafe impl<P: MutexPtr + Sync> Sync for PtrMutexGuard<P> where P::Data: Send + Sync {}
+unsafe impl<P: MutexPtr + Send, U: ?Sized + Send> Send for PtrMappedMutexGuard<P, U> where P::Data: Send {}
+unsafe impl<P: MutexPtr + Sync, U: ?Sized + Sync> Sync for PtrMappedMutexGuard<P, U> where P::Data: Send {}
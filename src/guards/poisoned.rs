@@ -0,0 +1,42 @@
+use core::task::Poll;
+use futures::{Future, future::FusedFuture};
+use crate::{LockResult, MutexPtr, poison};
+use super::{MutexFuture, MutexGuard, PtrMutexFuture, PtrMutexGuard};
+
+/// Future of the ```_poisoned``` lock methods, which resolves to an error if a thread panicked while holding the lock
+pub struct PoisonFuture<F> {
+    pub(crate) lock: F
+}
+
+impl<'a, T: ?Sized> Future for PoisonFuture<MutexFuture<'a, T>> {
+    type Output = LockResult<MutexGuard<'a, T>>;
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let lock = unsafe { self.map_unchecked_mut(|x| &mut x.lock) };
+        match lock.poll(cx) {
+            Poll::Ready(guard) => Poll::Ready(poison::locked(guard.is_poisoned(), guard)),
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+impl<P: MutexPtr> Future for PoisonFuture<PtrMutexFuture<P>> {
+    type Output = LockResult<PtrMutexGuard<P>>;
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let lock = unsafe { self.map_unchecked_mut(|x| &mut x.lock) };
+        match lock.poll(cx) {
+            Poll::Ready(guard) => Poll::Ready(poison::locked(guard.is_poisoned(), guard)),
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+impl<F: FusedFuture> FusedFuture for PoisonFuture<F> where Self: Future {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.lock.is_terminated()
+    }
+}
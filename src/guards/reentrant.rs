@@ -0,0 +1,121 @@
+use core::{ops::Deref, task::Poll, marker::PhantomData, pin::Pin};
+use futures::{Future, future::FusedFuture};
+use crate::{ReentrantMutex, OwnerId, queue::Node};
+
+/// Guard of a [```ReentrantMutex```] held by a thread. It can't be sent to another thread, since the thread may lock the mutex again.
+pub struct ReentrantMutexGuard<'a, T: ?Sized> {
+    pub(crate) mutex: &'a ReentrantMutex<T>,
+    pub(crate) _phantom: PhantomData<*const ()>
+}
+
+/// Guard of a [```ReentrantMutex```] held by a task, made with [```ReentrantMutex::lock```]
+pub struct ReentrantMutexTaskGuard<'a, T: ?Sized> {
+    pub(crate) mutex: &'a ReentrantMutex<T>,
+    pub(crate) _phantom: PhantomData<*const ()>
+}
+
+impl<'a, T: ?Sized> ReentrantMutexGuard<'a, T> {
+    #[inline(always)]
+    pub fn unlock (self) {}
+
+    /// Returns how many guards the thread holds for the mutex, this one included
+    #[inline(always)]
+    pub fn depth (&self) -> usize {
+        self.mutex.depth.load(crate::shim::atomic::Ordering::Relaxed)
+    }
+}
+
+impl<'a, T: ?Sized> ReentrantMutexTaskGuard<'a, T> {
+    #[inline(always)]
+    pub fn unlock (self) {}
+
+    /// Returns how many guards the task holds for the mutex, this one included
+    #[inline(always)]
+    pub fn depth (&self) -> usize {
+        self.mutex.depth.load(crate::shim::atomic::Ordering::Relaxed)
+    }
+}
+
+impl<'a, T: ?Sized> Deref for ReentrantMutexGuard<'a, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.mutex.data.with(|data| unsafe { &*data })
+    }
+}
+
+impl<'a, T: ?Sized> Deref for ReentrantMutexTaskGuard<'a, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.mutex.data.with(|data| unsafe { &*data })
+    }
+}
+
+impl<'a, T: ?Sized> Drop for ReentrantMutexGuard<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.mutex.exit()
+    }
+}
+
+impl<'a, T: ?Sized> Drop for ReentrantMutexTaskGuard<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.mutex.exit()
+    }
+}
+
+// a thread guard stays on it's thread, since the thread may lock the mutex again while it's away
+unsafe impl<'a, T: ?Sized + Sync> Sync for ReentrantMutexGuard<'a, T> {}
+unsafe impl<'a, T: ?Sized + Sync> Send for ReentrantMutexTaskGuard<'a, T> {}
+unsafe impl<'a, T: ?Sized + Sync> Sync for ReentrantMutexTaskGuard<'a, T> {}
+
+/// Future that resolves to a [```ReentrantMutexTaskGuard```]
+pub struct ReentrantMutexFuture<'a, T: ?Sized> {
+    pub(crate) mutex: Option<&'a ReentrantMutex<T>>,
+    pub(crate) owner: OwnerId,
+    pub(crate) node: Node
+}
+
+impl<'a, T: ?Sized> Future for ReentrantMutexFuture<'a, T> {
+    type Output = ReentrantMutexTaskGuard<'a, T>;
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let mutex = if let Some(mutex) = this.mutex { mutex } else { panic!("Mutex future already consumed") };
+
+        // a waiter never holds the mutex, so there's only something to re-enter on the first poll
+        let node = unsafe { Pin::new_unchecked(&this.node) };
+        let reentered = !node.is_queued() && mutex.reenter(this.owner);
+        if reentered || mutex.inner.poll_lock(node, cx).is_ready() {
+            if !reentered {
+                mutex.entered(this.owner);
+            }
+
+            this.mutex = None;
+            return Poll::Ready(ReentrantMutexTaskGuard { mutex, _phantom: PhantomData });
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<'a, T: ?Sized> FusedFuture for ReentrantMutexFuture<'a, T> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.mutex.is_none()
+    }
+}
+
+impl<'a, T: ?Sized> Drop for ReentrantMutexFuture<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(mutex) = self.mutex {
+            mutex.inner.cancel(unsafe { Pin::new_unchecked(&self.node) })
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use core::{ops::{Deref, DerefMut}, task::Poll, pin::Pin};
+use futures::{Future, future::FusedFuture};
+use crate::{RwLock, queue::Node};
+
+#[repr(transparent)]
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    pub(crate) inner: &'a RwLock<T>
+}
+
+#[repr(transparent)]
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    pub(crate) inner: &'a RwLock<T>
+}
+
+impl<'a, T: ?Sized> RwLockReadGuard<'a, T> {
+    #[inline(always)]
+    pub fn unlock (self) {}
+}
+
+impl<'a, T: ?Sized> RwLockWriteGuard<'a, T> {
+    #[inline(always)]
+    pub fn unlock (self) {}
+
+    /// Turns the write guard into a read guard, without letting any other writer in between
+    #[inline(always)]
+    pub fn downgrade (self) -> RwLockReadGuard<'a, T> {
+        let inner = self.inner;
+        core::mem::forget(self);
+        unsafe { inner.inner.downgrade() }
+        RwLockReadGuard { inner }
+    }
+}
+
+impl<'a, T: ?Sized> Deref for RwLockReadGuard<'a, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.inner.data.with(|data| unsafe { &*data })
+    }
+}
+
+impl<'a, T: ?Sized> Deref for RwLockWriteGuard<'a, T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.inner.data.with(|data| unsafe { &*data })
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for RwLockWriteGuard<'a, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.inner.data.with_mut(|data| unsafe { &mut *data })
+    }
+}
+
+impl<'a, T: ?Sized> Drop for RwLockReadGuard<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { self.inner.inner.unlock_read(); }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for RwLockWriteGuard<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { self.inner.inner.unlock_write(); }
+    }
+}
+
+/// Future that resolves to a read guard
+pub struct RwLockReadFuture<'a, T: ?Sized> {
+    pub(crate) lock: Option<&'a RwLock<T>>,
+    pub(crate) node: Node
+}
+
+/// Future that resolves to a write guard
+pub struct RwLockWriteFuture<'a, T: ?Sized> {
+    pub(crate) lock: Option<&'a RwLock<T>>,
+    pub(crate) node: Node,
+    pub(crate) locked: bool
+}
+
+impl<'a, T: ?Sized> Future for RwLockReadFuture<'a, T> {
+    type Output = RwLockReadGuard<'a, T>;
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let lock = if let Some(lock) = this.lock { lock } else { panic!("RwLock future already consumed") };
+        if lock.inner.poll_read(unsafe { Pin::new_unchecked(&this.node) }, cx).is_ready() {
+            this.lock = None;
+            return Poll::Ready(RwLockReadGuard { inner: lock });
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<'a, T: ?Sized> Future for RwLockWriteFuture<'a, T> {
+    type Output = RwLockWriteGuard<'a, T>;
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let lock = if let Some(lock) = this.lock { lock } else { panic!("RwLock future already consumed") };
+        if lock.inner.poll_write(unsafe { Pin::new_unchecked(&this.node) }, &mut this.locked, cx).is_ready() {
+            this.lock = None;
+            return Poll::Ready(RwLockWriteGuard { inner: lock });
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<'a, T: ?Sized> FusedFuture for RwLockReadFuture<'a, T> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.lock.is_none()
+    }
+}
+
+impl<'a, T: ?Sized> FusedFuture for RwLockWriteFuture<'a, T> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.lock.is_none()
+    }
+}
+
+impl<'a, T: ?Sized> Drop for RwLockReadFuture<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(lock) = self.lock {
+            lock.inner.cancel_read(unsafe { Pin::new_unchecked(&self.node) })
+        }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for RwLockWriteFuture<'a, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(lock) = self.lock {
+            lock.inner.cancel_write(unsafe { Pin::new_unchecked(&self.node) }, self.locked)
+        }
+    }
+}
+
+// readers only ever get shared access, so the data only needs to be ```Sync```
+unsafe impl<'a, T: ?Sized + Sync> Send for RwLockReadGuard<'a, T> {}
+unsafe impl<'a, T: ?Sized + Sync> Sync for RwLockReadGuard<'a, T> {}
+// a write guard can be downgraded into a read guard, so sending it needs the data to be ```Sync``` too
+unsafe impl<'a, T: ?Sized + Send + Sync> Send for RwLockWriteGuard<'a, T> {}
+unsafe impl<'a, T: ?Sized + Sync> Sync for RwLockWriteGuard<'a, T> {}
+
+unsafe impl<'a, T: ?Sized + Sync> Send for RwLockReadFuture<'a, T> {}
+unsafe impl<'a, T: ?Sized + Sync> Sync for RwLockReadFuture<'a, T> {}
+
+flat_mod!(owned);
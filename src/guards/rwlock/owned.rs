@@ -0,0 +1,183 @@
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use core::{ops::{Deref, DerefMut}, task::Poll, mem::ManuallyDrop, pin::Pin};
+#[cfg(feature = "alloc")]
+use alloc::{rc::Rc, sync::Arc};
+use futures::{Future, future::FusedFuture};
+use crate::{RwLockPtr, queue::Node};
+#[cfg(feature = "alloc")]
+use crate::RwLock;
+
+/// Read guard that keeps the pointer the lock was locked through, made with [```RwLock::read_ptr```](crate::RwLock::read_ptr) and friends
+#[repr(transparent)]
+pub struct PtrRwLockReadGuard<P: RwLockPtr> {
+    pub(crate) inner: P
+}
+
+/// Write guard that keeps the pointer the lock was locked through, made with [```RwLock::write_ptr```](crate::RwLock::write_ptr) and friends
+#[repr(transparent)]
+pub struct PtrRwLockWriteGuard<P: RwLockPtr> {
+    pub(crate) inner: P
+}
+
+/// Owned read guard that keeps the lock alive through an [```Rc```]
+#[cfg(feature = "alloc")]
+pub type OwnedRwLockReadGuard<T> = PtrRwLockReadGuard<Rc<RwLock<T>>>;
+/// Owned write guard that keeps the lock alive through an [```Rc```]
+#[cfg(feature = "alloc")]
+pub type OwnedRwLockWriteGuard<T> = PtrRwLockWriteGuard<Rc<RwLock<T>>>;
+/// Owned read guard that keeps the lock alive through an [```Arc```]
+#[cfg(feature = "alloc")]
+pub type AtomicRwLockReadGuard<T> = PtrRwLockReadGuard<Arc<RwLock<T>>>;
+/// Owned write guard that keeps the lock alive through an [```Arc```]
+#[cfg(feature = "alloc")]
+pub type AtomicRwLockWriteGuard<T> = PtrRwLockWriteGuard<Arc<RwLock<T>>>;
+
+impl<P: RwLockPtr> PtrRwLockReadGuard<P> {
+    #[inline(always)]
+    pub fn unlock (self) {}
+}
+
+impl<P: RwLockPtr> PtrRwLockWriteGuard<P> {
+    #[inline(always)]
+    pub fn unlock (self) {}
+
+    /// Turns the write guard into a read guard, without letting any other writer in between
+    #[inline(always)]
+    pub fn downgrade (self) -> PtrRwLockReadGuard<P> {
+        let this = ManuallyDrop::new(self);
+        let inner = unsafe { core::ptr::read(&this.inner) };
+        unsafe { inner.inner.downgrade() }
+        PtrRwLockReadGuard { inner }
+    }
+}
+
+impl<P: RwLockPtr> Deref for PtrRwLockReadGuard<P> {
+    type Target = P::Data;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.inner.data.with(|data| unsafe { &*data })
+    }
+}
+
+impl<P: RwLockPtr> Deref for PtrRwLockWriteGuard<P> {
+    type Target = P::Data;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.inner.data.with(|data| unsafe { &*data })
+    }
+}
+
+impl<P: RwLockPtr> DerefMut for PtrRwLockWriteGuard<P> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.inner.data.with_mut(|data| unsafe { &mut *data })
+    }
+}
+
+impl<P: RwLockPtr> Drop for PtrRwLockReadGuard<P> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { self.inner.inner.unlock_read(); }
+    }
+}
+
+impl<P: RwLockPtr> Drop for PtrRwLockWriteGuard<P> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { self.inner.inner.unlock_write(); }
+    }
+}
+
+/// Future that resolves to a [```PtrRwLockReadGuard```]
+pub struct PtrRwLockReadFuture<P: RwLockPtr> {
+    pub(crate) lock: Option<P>,
+    pub(crate) node: Node
+}
+
+/// Future that resolves to a [```PtrRwLockWriteGuard```]
+pub struct PtrRwLockWriteFuture<P: RwLockPtr> {
+    pub(crate) lock: Option<P>,
+    pub(crate) node: Node,
+    pub(crate) locked: bool
+}
+
+/// Future that resolves to an [```OwnedRwLockReadGuard```]
+#[cfg(feature = "alloc")]
+pub type OwnedRwLockReadFuture<T> = PtrRwLockReadFuture<Rc<RwLock<T>>>;
+/// Future that resolves to an [```OwnedRwLockWriteGuard```]
+#[cfg(feature = "alloc")]
+pub type OwnedRwLockWriteFuture<T> = PtrRwLockWriteFuture<Rc<RwLock<T>>>;
+/// Future that resolves to an [```AtomicRwLockReadGuard```]
+#[cfg(feature = "alloc")]
+pub type AtomicRwLockReadFuture<T> = PtrRwLockReadFuture<Arc<RwLock<T>>>;
+/// Future that resolves to an [```AtomicRwLockWriteGuard```]
+#[cfg(feature = "alloc")]
+pub type AtomicRwLockWriteFuture<T> = PtrRwLockWriteFuture<Arc<RwLock<T>>>;
+
+impl<P: RwLockPtr> Future for PtrRwLockReadFuture<P> {
+    type Output = PtrRwLockReadGuard<P>;
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let lock = if let Some(ref lock) = this.lock { lock } else { panic!("RwLock future already consumed") };
+        if lock.inner.poll_read(unsafe { Pin::new_unchecked(&this.node) }, cx).is_ready() {
+            let lock = core::mem::take(&mut this.lock).unwrap();
+            return Poll::Ready(PtrRwLockReadGuard { inner: lock });
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<P: RwLockPtr> Future for PtrRwLockWriteFuture<P> {
+    type Output = PtrRwLockWriteGuard<P>;
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let lock = if let Some(ref lock) = this.lock { lock } else { panic!("RwLock future already consumed") };
+        if lock.inner.poll_write(unsafe { Pin::new_unchecked(&this.node) }, &mut this.locked, cx).is_ready() {
+            let lock = core::mem::take(&mut this.lock).unwrap();
+            return Poll::Ready(PtrRwLockWriteGuard { inner: lock });
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<P: RwLockPtr> FusedFuture for PtrRwLockReadFuture<P> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.lock.is_none()
+    }
+}
+
+impl<P: RwLockPtr> FusedFuture for PtrRwLockWriteFuture<P> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.lock.is_none()
+    }
+}
+
+impl<P: RwLockPtr> Drop for PtrRwLockReadFuture<P> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(ref lock) = self.lock {
+            lock.inner.cancel_read(unsafe { Pin::new_unchecked(&self.node) })
+        }
+    }
+}
+
+impl<P: RwLockPtr> Drop for PtrRwLockWriteFuture<P> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(ref lock) = self.lock {
+            lock.inner.cancel_write(unsafe { Pin::new_unchecked(&self.node) }, self.locked)
+        }
+    }
+}
@@ -0,0 +1,75 @@
+extern crate alloc;
+use core::{task::Poll, pin::Pin};
+use alloc::sync::Arc;
+use futures::{Future, future::FusedFuture};
+use crate::{Semaphore, queue::Node};
+
+pub struct AtomicSemaphorePermit {
+    pub(crate) inner: Arc<Semaphore>,
+    pub(crate) permits: usize
+}
+
+impl AtomicSemaphorePermit {
+    /// Returns the number of permits held by the guard
+    #[inline(always)]
+    pub fn permits (&self) -> usize {
+        self.permits
+    }
+
+    #[inline(always)]
+    pub fn release (self) {}
+
+    /// Drops the guard without giving it's permits back to the semaphore
+    #[inline(always)]
+    pub fn forget (mut self) {
+        self.permits = 0
+    }
+}
+
+impl Drop for AtomicSemaphorePermit {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.inner.add_permits(self.permits)
+        }
+    }
+}
+
+/// Future that resolves to an atomic semaphore permit
+pub struct AtomicSemaphoreFuture {
+    pub(crate) semaphore: Option<Arc<Semaphore>>,
+    pub(crate) permits: usize,
+    pub(crate) node: Node
+}
+
+impl Future for AtomicSemaphoreFuture {
+    type Output = AtomicSemaphorePermit;
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let semaphore = if let Some(ref semaphore) = this.semaphore { semaphore } else { panic!("Semaphore future already consumed") };
+        if semaphore.poll_acquire(this.permits, unsafe { Pin::new_unchecked(&this.node) }, cx).is_ready() {
+            let semaphore = core::mem::take(&mut this.semaphore).unwrap();
+            return Poll::Ready(AtomicSemaphorePermit { inner: semaphore, permits: this.permits });
+        }
+
+        Poll::Pending
+    }
+}
+
+impl FusedFuture for AtomicSemaphoreFuture {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.semaphore.is_none()
+    }
+}
+
+impl Drop for AtomicSemaphoreFuture {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(ref semaphore) = self.semaphore {
+            semaphore.cancel(self.permits, unsafe { Pin::new_unchecked(&self.node) })
+        }
+    }
+}
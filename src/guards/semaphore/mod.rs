@@ -0,0 +1,74 @@
+use core::{task::Poll, pin::Pin};
+use futures::{Future, future::FusedFuture};
+use crate::{Semaphore, queue::Node};
+
+pub struct SemaphorePermit<'a> {
+    pub(crate) inner: &'a Semaphore,
+    pub(crate) permits: usize
+}
+
+impl<'a> SemaphorePermit<'a> {
+    /// Returns the number of permits held by the guard
+    #[inline(always)]
+    pub fn permits (&self) -> usize {
+        self.permits
+    }
+
+    #[inline(always)]
+    pub fn release (self) {}
+
+    /// Drops the guard without giving it's permits back to the semaphore
+    #[inline(always)]
+    pub fn forget (self) {
+        core::mem::forget(self)
+    }
+}
+
+impl<'a> Drop for SemaphorePermit<'a> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.inner.add_permits(self.permits)
+    }
+}
+
+/// Future that resolves to a semaphore permit
+pub struct SemaphoreFuture<'a> {
+    pub(crate) semaphore: Option<&'a Semaphore>,
+    pub(crate) permits: usize,
+    pub(crate) node: Node
+}
+
+impl<'a> Future for SemaphoreFuture<'a> {
+    type Output = SemaphorePermit<'a>;
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let semaphore = if let Some(semaphore) = this.semaphore { semaphore } else { panic!("Semaphore future already consumed") };
+        if semaphore.poll_acquire(this.permits, unsafe { Pin::new_unchecked(&this.node) }, cx).is_ready() {
+            this.semaphore = None;
+            return Poll::Ready(SemaphorePermit { inner: semaphore, permits: this.permits });
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<'a> FusedFuture for SemaphoreFuture<'a> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.semaphore.is_none()
+    }
+}
+
+impl<'a> Drop for SemaphoreFuture<'a> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(semaphore) = self.semaphore {
+            semaphore.cancel(self.permits, unsafe { Pin::new_unchecked(&self.node) })
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+flat_mod!(owned, atomic);
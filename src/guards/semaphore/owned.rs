@@ -0,0 +1,75 @@
+extern crate alloc;
+use core::{task::Poll, pin::Pin};
+use alloc::rc::Rc;
+use futures::{Future, future::FusedFuture};
+use crate::{Semaphore, queue::Node};
+
+pub struct OwnedSemaphorePermit {
+    pub(crate) inner: Rc<Semaphore>,
+    pub(crate) permits: usize
+}
+
+impl OwnedSemaphorePermit {
+    /// Returns the number of permits held by the guard
+    #[inline(always)]
+    pub fn permits (&self) -> usize {
+        self.permits
+    }
+
+    #[inline(always)]
+    pub fn release (self) {}
+
+    /// Drops the guard without giving it's permits back to the semaphore
+    #[inline(always)]
+    pub fn forget (mut self) {
+        self.permits = 0
+    }
+}
+
+impl Drop for OwnedSemaphorePermit {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.inner.add_permits(self.permits)
+        }
+    }
+}
+
+/// Future that resolves to an owned semaphore permit
+pub struct OwnedSemaphoreFuture {
+    pub(crate) semaphore: Option<Rc<Semaphore>>,
+    pub(crate) permits: usize,
+    pub(crate) node: Node
+}
+
+impl Future for OwnedSemaphoreFuture {
+    type Output = OwnedSemaphorePermit;
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let semaphore = if let Some(ref semaphore) = this.semaphore { semaphore } else { panic!("Semaphore future already consumed") };
+        if semaphore.poll_acquire(this.permits, unsafe { Pin::new_unchecked(&this.node) }, cx).is_ready() {
+            let semaphore = core::mem::take(&mut this.semaphore).unwrap();
+            return Poll::Ready(OwnedSemaphorePermit { inner: semaphore, permits: this.permits });
+        }
+
+        Poll::Pending
+    }
+}
+
+impl FusedFuture for OwnedSemaphoreFuture {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.semaphore.is_none()
+    }
+}
+
+impl Drop for OwnedSemaphoreFuture {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(ref semaphore) = self.semaphore {
+            semaphore.cancel(self.permits, unsafe { Pin::new_unchecked(&self.node) })
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use core::{task::Poll, pin::Pin};
+use futures::{Future, future::FusedFuture};
+
+/// Future of the ```lock_timeout``` family of methods, which gives up on the lock once it's timer fires
+pub struct LockTimeoutFuture<F, D> {
+    pub(crate) lock: Option<F>,
+    pub(crate) delay: D
+}
+
+impl<F: Future, D: Future<Output = ()>> Future for LockTimeoutFuture<F, D> {
+    type Output = Option<F::Output>;
+
+    #[inline]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let lock = match this.lock {
+            Some(ref mut lock) => unsafe { Pin::new_unchecked(lock) },
+            None => panic!("Mutex future already consumed")
+        };
+
+        if let Poll::Ready(locked) = lock.poll(cx) {
+            this.lock = None;
+            return Poll::Ready(Some(locked));
+        }
+
+        if unsafe { Pin::new_unchecked(&mut this.delay) }.poll(cx).is_ready() {
+            // dropping the lock's future takes it off the queue, passing on any wakeup (or ownership) it was given
+            this.lock = None;
+            return Poll::Ready(None);
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<F: Future, D: Future<Output = ()>> FusedFuture for LockTimeoutFuture<F, D> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.lock.is_none()
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use core::pin::Pin;
+#[cfg(feature = "alloc")]
+use alloc::{rc::Rc, sync::Arc};
+use crate::{Mutex, MutexPtr, queue::Node};
+
+/// A waiter's place in a mutex's queue, for locking it from hand-written futures and streams with [```Mutex::poll_lock```] and friends.
+///
+/// Unlike a mutex future, it doesn't need to be made from the mutex, so it can live next to it (or to a pointer to it) in the same struct.
+/// It's reused across locks, and while it's waiting it keeps a copy of the pointer the mutex is being locked through.
+/// Dropping it while it's waiting takes it off the queue, passing on any wakeup (or ownership) it was given.
+///
+/// It must be pinned, since the queue points to it while it's waiting. Use ```Box::pin``` to keep it in an ```Unpin``` struct.
+pub struct PtrMutexWaiter<P: MutexPtr> {
+    pub(crate) mutex: Option<P>,
+    pub(crate) node: Node
+}
+
+/// Waiter for [```Mutex::poll_lock```]
+pub type MutexWaiter<'a, T> = PtrMutexWaiter<&'a Mutex<T>>;
+/// Waiter for [```Mutex::poll_lock_owned```]
+#[cfg(feature = "alloc")]
+pub type OwnedMutexWaiter<T> = PtrMutexWaiter<Rc<Mutex<T>>>;
+/// Waiter for [```Mutex::poll_lock_atomic```]
+#[cfg(feature = "alloc")]
+pub type AtomicMutexWaiter<T> = PtrMutexWaiter<Arc<Mutex<T>>>;
+
+impl<P: MutexPtr> PtrMutexWaiter<P> {
+    loom_const_fn! {
+        /// Creates a new waiter, which isn't waiting for any mutex yet
+        #[inline(always)]
+        pub fn new () -> Self {
+            Self {
+                mutex: None,
+                node: Node::new()
+            }
+        }
+    }
+
+    /// Returns ```true``` if the waiter is on a mutex's queue
+    #[inline(always)]
+    pub fn is_waiting (&self) -> bool {
+        self.mutex.is_some()
+    }
+
+    /// Takes the waiter off the mutex's queue, if it's on one.
+    /// Any wakeup (or ownership) it was given is passed on to the next waiter.
+    #[inline]
+    pub fn cancel (self: Pin<&mut Self>) {
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Some(mutex) = this.mutex.take() {
+            mutex.inner.cancel(unsafe { Pin::new_unchecked(&this.node) })
+        }
+    }
+
+    /// Returns the waiter's node, after checking that it isn't waiting for a mutex other than ```mutex```
+    #[inline]
+    pub(crate) fn node_for (&self, mutex: &Mutex<P::Data>) -> Pin<&Node> {
+        if let Some(ref waiting) = self.mutex {
+            assert!(core::ptr::eq(&**waiting, mutex), "waiter is already waiting for another mutex");
+        }
+
+        unsafe { Pin::new_unchecked(&self.node) }
+    }
+}
+
+impl<P: MutexPtr> Default for PtrMutexWaiter<P> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: MutexPtr> Drop for PtrMutexWaiter<P> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(ref mutex) = self.mutex {
+            mutex.inner.cancel(unsafe { Pin::new_unchecked(&self.node) })
+        }
+    }
+}
@@ -1,5 +1,15 @@
 #![no_std]
-use core::sync::atomic::*;
+use shim::atomic::*;
+
+/// Declares a ```const fn```, which isn't ```const``` under ```cfg(loom)``` since loom's atomics can't be created in a const context
+macro_rules! loom_const_fn {
+    ($(#[$meta:meta])* $vis:vis fn $($rest:tt)*) => {
+        #[cfg(not(loom))]
+        $(#[$meta])* $vis const fn $($rest)*
+        #[cfg(loom)]
+        $(#[$meta])* $vis fn $($rest)*
+    };
+}
 
 macro_rules! flat_mod {
     ($($i:ident),+) => {
@@ -36,9 +46,23 @@ cfg_if::cfg_if! {
     }
 }
 
-flat_mod!(regular);
+flat_mod!(regular, rwlock, semaphore, condvar, lock_all, ptr, timer, reentrant);
 pub mod movable;
 pub mod guards;
 
+pub(crate) mod shim;
 pub(crate) mod waker;
-pub(crate) mod queue;
\ No newline at end of file
+pub(crate) mod queue;
+pub(crate) mod poison;
+pub(crate) mod clock;
+pub(crate) mod stats;
+pub(crate) mod trace;
+pub(crate) mod deadlock;
+pub(crate) mod owner;
+
+#[cfg(feature = "poison")]
+pub use poison::{PoisonError, TryLockError, LockResult, TryLockResult};
+#[cfg(feature = "stats")]
+pub use stats::MutexStats;
+#[cfg(feature = "deadlock-detection")]
+pub use deadlock::{LockId, LockOrderViolation, set_lock_order_handler};
\ No newline at end of file
@@ -0,0 +1,219 @@
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::{rc::Rc, sync::Arc, vec::Vec};
+use crate::{Mutex, guards::*, movable::MovableMutex, queue::Node};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A mutex (or a pointer to one) that can be locked as part of a [```lock_all```]
+pub trait Lockable: sealed::Sealed {
+    /// Guard returned once the mutex is locked
+    type Guard;
+
+    /// Returns the mutex to lock, which must stay at the same address for as long as ```self``` is alive
+    #[doc(hidden)]
+    fn mutex (&self) -> *const MovableMutex;
+
+    /// # Safety
+    /// The mutex must be locked by the caller
+    #[doc(hidden)]
+    unsafe fn into_guard (self) -> Self::Guard;
+}
+
+/// A set of mutexes that can be locked together with [```lock_all```] or ```lock_all_blocking```.
+///
+/// It's implemented for tuples (of up to 8 elements) and arrays of [```Lockable```]s,
+/// and with the ```alloc``` feature, for slices and vectors of them.
+pub trait LockAll: sealed::Sealed {
+    /// Guards returned once every mutex is locked
+    type Guards;
+
+    #[doc(hidden)]
+    type Order: AsMut<[*const MovableMutex]>;
+
+    /// Returns the mutexes to lock, in any order
+    #[doc(hidden)]
+    fn order (&self) -> Self::Order;
+
+    /// # Safety
+    /// Every mutex must be locked by the caller
+    #[doc(hidden)]
+    unsafe fn into_guards (self) -> Self::Guards;
+}
+
+/// Returns a future that locks every mutex in ```mutexes```, resolving to all of their guards.
+///
+/// Mutexes are always locked in the same global order (by address), regardless of the order they're passed in,
+/// so two tasks locking the same set of mutexes can't deadlock each other.
+///
+/// # Panics
+/// Panics if the same mutex is passed more than once
+#[inline(always)]
+pub fn lock_all<L: LockAll> (mutexes: L) -> LockAllFuture<L> {
+    let order = sorted(&mutexes);
+    LockAllFuture {
+        mutexes: Some(mutexes),
+        order,
+        locked: 0,
+        node: Node::new()
+    }
+}
+
+/// Blocks the current thread until every mutex in ```mutexes``` is locked, returning all of their guards.
+/// See [```lock_all```] for the order they're locked in.
+///
+/// # Panics
+/// Panics if the same mutex is passed more than once
+#[cfg(feature = "sync")]
+#[inline]
+pub fn lock_all_blocking<L: LockAll> (mutexes: L) -> L::Guards {
+    let mut order = sorted(&mutexes);
+    for mutex in order.as_mut() {
+        unsafe { &**mutex }.lock_blocking_raw()
+    }
+
+    unsafe { mutexes.into_guards() }
+}
+
+#[inline]
+fn sorted<L: LockAll> (mutexes: &L) -> L::Order {
+    let mut order = mutexes.order();
+    let slice = order.as_mut();
+    slice.sort_unstable_by_key(|x| *x as usize);
+    assert!(slice.windows(2).all(|x| x[0] != x[1]), "the same mutex can't be locked twice");
+    order
+}
+
+impl<T: ?Sized> sealed::Sealed for &Mutex<T> {}
+impl<'a, T: ?Sized> Lockable for &'a Mutex<T> {
+    type Guard = MutexGuard<'a, T>;
+
+    #[inline(always)]
+    fn mutex (&self) -> *const MovableMutex {
+        &self.inner
+    }
+
+    #[inline(always)]
+    unsafe fn into_guard (self) -> Self::Guard {
+        MutexGuard::new(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ?Sized> sealed::Sealed for Rc<Mutex<T>> {}
+#[cfg(feature = "alloc")]
+impl<T: ?Sized> Lockable for Rc<Mutex<T>> {
+    type Guard = OwnedMutexGuard<T>;
+
+    #[inline(always)]
+    fn mutex (&self) -> *const MovableMutex {
+        &self.inner
+    }
+
+    #[inline(always)]
+    unsafe fn into_guard (self) -> Self::Guard {
+        OwnedMutexGuard::new(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ?Sized> sealed::Sealed for Arc<Mutex<T>> {}
+#[cfg(feature = "alloc")]
+impl<T: ?Sized> Lockable for Arc<Mutex<T>> {
+    type Guard = AtomicMutexGuard<T>;
+
+    #[inline(always)]
+    fn mutex (&self) -> *const MovableMutex {
+        &self.inner
+    }
+
+    #[inline(always)]
+    unsafe fn into_guard (self) -> Self::Guard {
+        AtomicMutexGuard::new(self)
+    }
+}
+
+macro_rules! impl_tuple {
+    ($len:literal => $($t:ident $i:tt),+) => {
+        impl<$($t: Lockable),+> sealed::Sealed for ($($t,)+) {}
+        impl<$($t: Lockable),+> LockAll for ($($t,)+) {
+            type Guards = ($($t::Guard,)+);
+            type Order = [*const MovableMutex; $len];
+
+            #[inline(always)]
+            fn order (&self) -> Self::Order {
+                [$(self.$i.mutex()),+]
+            }
+
+            #[inline(always)]
+            unsafe fn into_guards (self) -> Self::Guards {
+                ($(self.$i.into_guard(),)+)
+            }
+        }
+    };
+}
+
+impl_tuple!(1 => A 0);
+impl_tuple!(2 => A 0, B 1);
+impl_tuple!(3 => A 0, B 1, C 2);
+impl_tuple!(4 => A 0, B 1, C 2, D 3);
+impl_tuple!(5 => A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(6 => A 0, B 1, C 2, D 3, E 4, F 5);
+impl_tuple!(7 => A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_tuple!(8 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+impl<L: Lockable, const N: usize> sealed::Sealed for [L; N] {}
+impl<L: Lockable, const N: usize> LockAll for [L; N] {
+    type Guards = [L::Guard; N];
+    type Order = [*const MovableMutex; N];
+
+    #[inline(always)]
+    fn order (&self) -> Self::Order {
+        core::array::from_fn(|i| self[i].mutex())
+    }
+
+    #[inline(always)]
+    unsafe fn into_guards (self) -> Self::Guards {
+        self.map(|x| x.into_guard())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<L: Lockable> sealed::Sealed for Vec<L> {}
+#[cfg(feature = "alloc")]
+impl<L: Lockable> LockAll for Vec<L> {
+    type Guards = Vec<L::Guard>;
+    type Order = Vec<*const MovableMutex>;
+
+    #[inline(always)]
+    fn order (&self) -> Self::Order {
+        self.iter().map(L::mutex).collect()
+    }
+
+    #[inline(always)]
+    unsafe fn into_guards (self) -> Self::Guards {
+        self.into_iter().map(|x| x.into_guard()).collect()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<L: Lockable + Clone> sealed::Sealed for &[L] {}
+#[cfg(feature = "alloc")]
+impl<L: Lockable + Clone> LockAll for &[L] {
+    type Guards = Vec<L::Guard>;
+    type Order = Vec<*const MovableMutex>;
+
+    #[inline(always)]
+    fn order (&self) -> Self::Order {
+        self.iter().map(L::mutex).collect()
+    }
+
+    #[inline(always)]
+    unsafe fn into_guards (self) -> Self::Guards {
+        self.iter().map(|x| x.clone().into_guard()).collect()
+    }
+}
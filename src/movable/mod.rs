@@ -0,0 +1,531 @@
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+use core::{sync::atomic::Ordering, task::{Poll, Context}, fmt::Debug, pin::Pin};
+#[cfg(feature = "alloc")]
+use alloc::{rc::Rc, sync::Arc};
+use crate::{shim::atomic::AtomicUsize, guards::*, queue::{Queue, Entries, Node, WaitState}, stats::Stats, deadlock::{Tracker, Holder}, owner::Owner, clock::AtomicStamp, trace};
+#[cfg(feature = "sync")]
+use crate::waker::{Parker, Deadline};
+#[cfg(all(feature = "std", not(loom)))]
+use std::time::{Duration, Instant};
+
+/// Decides who gets a mutex when it's unlocked while others are waiting for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Fairness {
+    /// The mutex is released and the first waiter is woken, but anyone may take the mutex before it gets to run
+    #[default]
+    Barging,
+    /// Ownership is handed straight to the first waiter, so waiters acquire the mutex in strict FIFO order
+    Fifo,
+    /// Like [```Barging```](Fairness::Barging), but a waiter that has already been barged past
+    /// gets ownership handed straight to it on the next unlock, so no waiter can starve
+    Eventual
+}
+
+/// The mutex is locked
+const LOCKED : usize = 1;
+/// There may be waiters on the mutex's queue, so unlocking it has to go through the queue
+const QUEUED : usize = 2;
+
+/// A mutex that is not attached to any value
+pub struct MovableMutex {
+    /// [```LOCKED```] and [```QUEUED```] bits
+    pub(crate) state: AtomicUsize,
+    pub(crate) queue: Queue,
+    pub(crate) fairness: Fairness,
+    pub(crate) name: Option<&'static str>,
+    /// When the current owner acquired the mutex, to time how long it's held for
+    pub(crate) locked_at: AtomicStamp,
+    pub(crate) stats: Stats,
+    pub(crate) tracker: Tracker,
+    pub(crate) owner: Owner
+}
+
+impl MovableMutex {
+    loom_const_fn! {
+        /// Creates a new mutex
+        #[inline(always)]
+        pub fn new () -> Self {
+            Self::with_fairness(Fairness::Barging)
+        }
+    }
+
+    loom_const_fn! {
+        /// Creates a new mutex with the specified fairness policy
+        #[inline(always)]
+        pub fn with_fairness (fairness: Fairness) -> Self {
+            Self { 
+                state: AtomicUsize::new(0),
+                queue: Queue::new(),
+                fairness,
+                name: None,
+                locked_at: AtomicStamp::new(),
+                stats: Stats::new(),
+                tracker: Tracker::new(),
+                owner: Owner::new()
+            }
+        }
+    }
+
+    loom_const_fn! {
+        /// Creates a new mutex that starts locked
+        #[inline(always)]
+        pub fn locked () -> Self {
+            Self { 
+                state: AtomicUsize::new(LOCKED),
+                queue: Queue::new(),
+                fairness: Fairness::Barging,
+                name: None,
+                locked_at: AtomicStamp::new(),
+                stats: Stats::new(),
+                tracker: Tracker::new(),
+                owner: Owner::locked()
+            }
+        }
+    }
+
+    /// Gives the mutex a name, which shows up in it's ```Debug``` output and (with the ```tracing``` feature) in it's events
+    #[inline(always)]
+    pub const fn named (mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Returns the mutex's fairness policy
+    #[inline(always)]
+    pub fn fairness (&self) -> Fairness {
+        self.fairness
+    }
+
+    /// Returns the mutex's name, if it has one
+    #[inline(always)]
+    pub fn name (&self) -> Option<&'static str> {
+        self.name
+    }
+
+    /// Returns ```true``` if anyone is waiting for the mutex.
+    /// It's only a hint, since waiters may come and go at any time.
+    #[inline(always)]
+    pub fn has_waiters (&self) -> bool {
+        self.state.load(Ordering::Relaxed) & QUEUED != 0
+    }
+
+    /// Returns ```true``` if the mutex is currently locked
+    #[inline(always)]
+    pub(crate) fn is_locked (&self) -> bool {
+        self.state.load(Ordering::Relaxed) & LOCKED != 0
+    }
+
+    /// Attempts to lock the mutex, returning it's lock token if it's successful, and ```None``` otherwise
+    #[inline(always)]
+    pub fn try_lock (&self) -> Option<MovableMutexGuard<'_>> {
+        match self.try_lock_raw() {
+            true => Some(MovableMutexGuard { mutex: self }),
+            false => None
+        }
+    }
+
+    /// Locks the mutex if it's unlocked, without making a lock token
+    #[inline(always)]
+    pub(crate) fn try_lock_raw (&self) -> bool {
+        if self.acquire() {
+            self.acquired(Holder::thread(), None, false);
+            return true
+        }
+
+        false
+    }
+
+    /// Returns a snapshot of the mutex's contention statistics
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn stats (&self) -> crate::MutexStats {
+        self.stats.get()
+    }
+
+    /// Resets the mutex's contention statistics
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn reset_stats (&self) {
+        self.stats.reset()
+    }
+
+    /// Returns the mutex's identity in the lock-order graph, as reported by a [```LockOrderViolation```](crate::LockOrderViolation)
+    #[cfg(feature = "deadlock-detection")]
+    #[inline(always)]
+    pub fn lock_id (&self) -> crate::LockId {
+        self.tracker.id()
+    }
+
+    /// Blocks the current thread until the mutex is acquired, returning it's lock token.
+    /// With the ```std``` feature the thread is parked while it waits, otherwise it spins.
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn lock_blocking (&self) -> MovableMutexGuard<'_> {
+        self.lock_blocking_raw();
+        MovableMutexGuard { mutex: self }
+    }
+
+    /// Blocks the current thread until the mutex is acquired, without making a lock token
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub(crate) fn lock_blocking_raw (&self) {
+        self.lock_blocking_until_raw(None);
+    }
+
+    /// Blocks the current thread until the mutex is acquired or ```timeout``` has passed, returning it's lock token if it's acquired
+    #[cfg(all(feature = "std", not(loom)))]
+    #[inline(always)]
+    pub fn lock_blocking_timeout (&self, timeout: Duration) -> Option<MovableMutexGuard<'_>> {
+        self.lock_blocking_timeout_raw(timeout).then_some(MovableMutexGuard { mutex: self })
+    }
+
+    /// Blocks the current thread until the mutex is acquired or ```deadline``` is reached, returning it's lock token if it's acquired
+    #[cfg(all(feature = "std", not(loom)))]
+    #[inline(always)]
+    pub fn lock_blocking_until (&self, deadline: Instant) -> Option<MovableMutexGuard<'_>> {
+        self.lock_blocking_until_raw(Some(deadline)).then_some(MovableMutexGuard { mutex: self })
+    }
+
+    #[cfg(all(feature = "std", not(loom)))]
+    #[inline(always)]
+    pub(crate) fn lock_blocking_timeout_raw (&self, timeout: Duration) -> bool {
+        self.lock_blocking_until_raw(Instant::now().checked_add(timeout))
+    }
+
+    /// Blocks the current thread until the mutex is acquired or ```deadline``` (if any) is reached, returning ```true``` if it's acquired
+    #[cfg(feature = "sync")]
+    pub(crate) fn lock_blocking_until_raw (&self, deadline: Option<Deadline>) -> bool {
+        self.requested(Holder::thread(), true);
+        if self.try_lock_raw() { return true; }
+
+        let parker = Parker::current();
+        let node = core::pin::pin!(Node::new());
+        self.waiting(&node, true);
+
+        loop {
+            if self.register(|queue| queue.register_sync(node.as_ref(), &parker)) { break; }
+            self.stats.queued(&self.queue);
+            // the mutex may have been unlocked before we were queued, in which case nobody will wake us
+            if self.acquire() {
+                self.remove(node.as_ref());
+                break;
+            }
+
+            if !parker.park_until(deadline) {
+                // leaving the queue passes on any wakeup (or ownership) we were given in the meantime
+                self.cancel(node.as_ref());
+                return false;
+            }
+        }
+
+        self.acquired(Holder::thread(), Some(&node), false);
+        true
+    }
+
+    /// Returns a future that resolves to the mutex's lock token once it's acquired
+    #[inline(always)]
+    pub fn lock (&self) -> MovableMutexFuture<'_> {
+        MovableMutexFuture {
+            mutex: Some(self),
+            node: Node::new()
+        }
+    }
+
+    /// Unlocks the mutex by hand. It's meant for mutexes whose lock token was [```forget```](MovableMutexGuard::forget)ed.
+    /// Who gets the mutex next is decided by it's [```Fairness```] policy.
+    ///
+    /// Without the ```checked``` feature, it doesn't check if this thread was it's owner.
+    /// With it, unlocking a mutex whose token is still alive, unlocking it on a different thread than the one that forgot it's token,
+    /// unlocking it twice, or unlocking a [```locked```](MovableMutex::locked) mutex that nobody acquired panics, naming both the caller and the owner.
+    /// That goes for tasks too, so a task should unlock the mutex before it yields after forgetting the token.
+    /// 
+    /// # Safety
+    /// The mutex must be locked, and the caller must be the one that locked it
+    #[inline(always)]
+    pub unsafe fn unlock (&self) {
+        self.owner.unlocked_by_hand(self.name);
+        self.unlock_raw()
+    }
+
+    /// Unlocks the mutex, handing ownership straight to the first waiter (if any), regardless of the mutex's [```Fairness```] policy.
+    /// It's checked like [```unlock```](MovableMutex::unlock).
+    /// 
+    /// # Safety
+    /// The mutex must be locked, and the caller must be the one that locked it
+    #[inline(always)]
+    pub unsafe fn unlock_fair (&self) {
+        self.owner.unlocked_by_hand(self.name);
+        self.unlock_fair_raw()
+    }
+
+    /// Unlocks the mutex on behalf of it's lock token (or of the crate), which owns it by construction
+    #[inline(always)]
+    pub(crate) unsafe fn unlock_raw (&self) {
+        match self.fairness {
+            Fairness::Barging => self.release(|_| false),
+            Fairness::Fifo => self.release(|_| true),
+            Fairness::Eventual => self.release(|barged| barged)
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) unsafe fn unlock_fair_raw (&self) {
+        self.release(|_| true)
+    }
+
+    /// Unlocks the mutex while ```f``` runs, blocking until it's locked again afterwards (even if ```f``` panics)
+    ///
+    /// # Safety
+    /// The mutex must be locked, and the caller must be the one that locked it
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) unsafe fn unlocked_blocking_raw<R, F: FnOnce() -> R> (&self, f: F) -> R {
+        struct Relock<'a> (&'a MovableMutex);
+
+        impl Drop for Relock<'_> {
+            #[inline(always)]
+            fn drop(&mut self) {
+                self.0.lock_blocking_raw()
+            }
+        }
+
+        self.unlock_raw();
+        let _relock = Relock(self);
+        f()
+    }
+
+    /// ```holder``` is about to lock the mutex with ```lock``` or ```lock_blocking```
+    #[inline(always)]
+    fn requested (&self, holder: Holder, blocking: bool) {
+        // every lock held by the holder must be locked before this one, whether it has to wait for it or not
+        self.tracker.acquiring(holder, self.name);
+        trace::requested(self, blocking);
+    }
+
+    /// The mutex was locked on the first attempt, so the waiter is about to be queued
+    #[inline(always)]
+    fn waiting (&self, node: &Node, blocking: bool) {
+        node.wait.start();
+        self.stats.waiting(blocking);
+        trace::contended(self, blocking);
+    }
+
+    /// The mutex was acquired, by a waiter with the node ```wait``` if it was contended
+    #[inline(always)]
+    fn acquired (&self, holder: Holder, wait: Option<&Node>, task: bool) {
+        let wait = wait.map(|node| node.wait.elapsed());
+        self.owner.acquired(task);
+        self.locked_at.start();
+        self.stats.acquired(wait);
+        self.tracker.acquired(holder, self.name);
+        trace::acquired(self, wait);
+    }
+
+    #[inline(always)]
+    fn released (&self) {
+        self.owner.released(self.name);
+        let hold = self.locked_at.take();
+        self.stats.released(hold);
+        self.tracker.released();
+        trace::released(self, hold);
+    }
+
+    #[inline(always)]
+    fn acquire (&self) -> bool {
+        self.state.fetch_or(LOCKED, Ordering::Acquire) & LOCKED == 0
+    }
+
+    /// Unlocks the mutex. Without waiters, it's a single atomic operation that doesn't touch the queue.
+    /// Otherwise, the first waiter is woken, and if ```handoff``` returns ```true``` (it's told whether the waiter
+    /// has already been barged past), ownership is handed straight to it instead.
+    #[inline]
+    fn release<F: FnOnce(bool) -> bool> (&self, handoff: F) {
+        self.released();
+        if self.state.compare_exchange(LOCKED, 0, Ordering::Release, Ordering::Relaxed).is_ok() {
+            return
+        }
+
+        // the decision is made while the queue is locked, so a waiter that's being queued can't miss it
+        let waker = self.queue.with(|queue| {
+            let (waker, handed) = queue.release(handoff);
+            if !handed {
+                let bits = match queue.is_empty() {
+                    true => LOCKED | QUEUED,
+                    false => LOCKED
+                };
+
+                #[cfg(any(debug_assertions, loom))]
+                assert_ne!(self.state.fetch_and(!bits, Ordering::Release) & LOCKED, 0);
+                #[cfg(not(any(debug_assertions, loom)))]
+                self.state.fetch_and(!bits, Ordering::Release);
+            }
+
+            waker
+        });
+
+        if let Some(waker) = waker {
+            waker.wake()
+        }
+    }
+
+    /// Runs ```f``` (which queues a waiter's node) while the queue is locked, keeping the [```QUEUED```] bit in sync with it
+    #[inline(always)]
+    fn register<F: FnOnce(&mut Entries<'_>) -> bool> (&self, f: F) -> bool {
+        self.queue.with(|queue| {
+            let handed = f(queue);
+            self.queued(queue);
+            handed
+        })
+    }
+
+    /// Removes ```node``` from the queue, keeping the [```QUEUED```] bit in sync with it
+    #[inline(always)]
+    fn remove (&self, node: Pin<&Node>) -> Option<WaitState> {
+        if !node.is_queued() { return None }
+        Some(self.queue.with(|queue| {
+            let state = queue.remove(node);
+            self.queued(queue);
+            state
+        }))
+    }
+
+    /// Sets or clears the [```QUEUED```] bit, depending on whether there's anyone left on the queue.
+    /// It's set before a waiter that was just queued re-checks the mutex, so an unlock either sees the bit and goes
+    /// through the queue, or happens before the re-check.
+    #[inline(always)]
+    fn queued (&self, queue: &Entries<'_>) {
+        match queue.is_empty() {
+            true => self.state.fetch_and(!QUEUED, Ordering::Relaxed),
+            false => self.state.fetch_or(QUEUED, Ordering::AcqRel)
+        };
+    }
+
+    /// Attempts to lock the mutex, queueing the task's waker on ```node``` if it's already locked.
+    /// A waiter keeps the same node (and it's place in the queue) across polls.
+    #[inline]
+    pub(crate) fn poll_lock (&self, node: Pin<&Node>, cx: &mut Context<'_>) -> Poll<()> {
+        let holder = Holder::task(cx.waker());
+        let first = !node.is_queued();
+        if first {
+            self.requested(holder, false);
+        }
+
+        if !self.acquire() {
+            if first {
+                self.waiting(&node, false);
+            }
+
+            if self.register(|queue| queue.register(node, cx.waker())) {
+                self.acquired(holder, Some(&node), true);
+                return Poll::Ready(());
+            }
+
+            self.stats.queued(&self.queue);
+            // re-checked for the same reason as in lock_blocking_until_raw
+            if !self.acquire() {
+                return Poll::Pending;
+            }
+        }
+
+        match self.remove(node) {
+            Some(_) => self.acquired(holder, Some(&node), true),
+            None => self.acquired(holder, None, true)
+        }
+
+        Poll::Ready(())
+    }
+
+    /// Removes the waiter's node from the queue. If it had already been woken, the wakeup is passed on to the next waiter,
+    /// and if it had been handed ownership, the mutex is unlocked.
+    #[inline]
+    pub(crate) fn cancel (&self, node: Pin<&Node>) {
+        match self.remove(node) {
+            None | Some(WaitState::Waiting) => {},
+            Some(WaitState::Woken) => self.queue.wake(),
+            Some(WaitState::HandedOff) => {
+                // the waiter owns the mutex now, even if it's giving it up right away
+                self.owner.acquired(false);
+                unsafe { self.unlock_raw() }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl MovableMutex {
+    /// Attempts to lock the mutex, returning a lock token that keeps it alive through an [```Rc```](alloc::rc::Rc) if it's successful
+    #[inline(always)]
+    pub fn try_lock_owned (self: Rc<Self>) -> Option<OwnedMovableMutexGuard> {
+        match self.try_lock_raw() {
+            true => Some(OwnedMovableMutexGuard { mutex: self }),
+            false => None
+        }
+    }
+
+    /// Blocks the current thread until the mutex is acquired, returning a lock token that keeps it alive through an [```Rc```](alloc::rc::Rc)
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn lock_blocking_owned (self: Rc<Self>) -> OwnedMovableMutexGuard {
+        self.lock_blocking_raw();
+        OwnedMovableMutexGuard { mutex: self }
+    }
+
+    /// Returns a future that resolves to a lock token that keeps the mutex alive through an [```Rc```](alloc::rc::Rc)
+    #[inline(always)]
+    pub fn lock_owned (self: Rc<Self>) -> OwnedMovableMutexFuture {
+        OwnedMovableMutexFuture {
+            mutex: Some(self),
+            node: Node::new()
+        }
+    }
+
+    /// Attempts to lock the mutex, returning a lock token that keeps it alive through an [```Arc```](alloc::sync::Arc) if it's successful
+    #[inline(always)]
+    pub fn try_lock_atomic (self: Arc<Self>) -> Option<AtomicMovableMutexGuard> {
+        match self.try_lock_raw() {
+            true => Some(AtomicMovableMutexGuard { mutex: self }),
+            false => None
+        }
+    }
+
+    /// Blocks the current thread until the mutex is acquired, returning a lock token that keeps it alive through an [```Arc```](alloc::sync::Arc)
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn lock_blocking_atomic (self: Arc<Self>) -> AtomicMovableMutexGuard {
+        self.lock_blocking_raw();
+        AtomicMovableMutexGuard { mutex: self }
+    }
+
+    /// Returns a future that resolves to a lock token that keeps the mutex alive through an [```Arc```](alloc::sync::Arc)
+    #[inline(always)]
+    pub fn lock_atomic (self: Arc<Self>) -> AtomicMovableMutexFuture {
+        AtomicMovableMutexFuture {
+            mutex: Some(self),
+            node: Node::new()
+        }
+    }
+}
+
+impl Default for MovableMutex {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for MovableMutex {
+    #[inline(always)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut f = f.debug_struct("MovableMutex");
+        if let Some(name) = self.name {
+            f.field("name", &name);
+        }
+
+        f.field("locked", &self.is_locked()).field("fairness", &self.fairness).finish()
+    }
+}
+
+flat_mod!(rwlock);
\ No newline at end of file
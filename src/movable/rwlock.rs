@@ -0,0 +1,337 @@
+use core::{task::{Poll, Context}, future::Future, fmt::Debug, pin::Pin};
+use futures::future::FusedFuture;
+use crate::{queue::{Queue, Node}, shim::atomic::{AtomicUsize, Ordering}};
+use super::MovableMutex;
+#[cfg(feature = "sync")]
+use crate::waker::Parker;
+
+/// Set while a writer holds (or is waiting for the readers to leave) the lock
+const WRITER : usize = 1;
+const ONE_READER : usize = 2;
+
+/// A readers-writer lock that is not attached to any value
+pub struct MovableRwLock {
+    /// Number of readers (in units of [```ONE_READER```]), plus the [```WRITER```] bit
+    pub(crate) state: AtomicUsize,
+    /// Held by the writer, so only one of them can wait for the readers to leave at a time
+    pub(crate) mutex: MovableMutex,
+    /// Readers waiting for the writer to leave
+    pub(crate) no_writer: Queue,
+    /// Writer waiting for the readers to leave
+    pub(crate) no_readers: Queue
+}
+
+impl MovableRwLock {
+    loom_const_fn! {
+        /// Creates a new readers-writer lock
+        #[inline(always)]
+        pub fn new () -> Self {
+            Self {
+                state: AtomicUsize::new(0),
+                mutex: MovableMutex::new(),
+                no_writer: Queue::new(),
+                no_readers: Queue::new()
+            }
+        }
+    }
+
+    /// Returns the number of readers currently holding the lock
+    #[inline(always)]
+    pub fn readers (&self) -> usize {
+        self.state.load(Ordering::Relaxed) / ONE_READER
+    }
+
+    /// Attempts to acquire a read lock, returning ```true``` if it's successful, and ```false``` otherwise
+    #[inline]
+    pub fn try_read (&self) -> bool {
+        let mut state = self.state.load(Ordering::Acquire);
+        loop {
+            if state & WRITER != 0 { return false }
+            let new = state.checked_add(ONE_READER).expect("too many readers");
+
+            match self.state.compare_exchange_weak(state, new, Ordering::Acquire, Ordering::Acquire) {
+                Ok(_) => return true,
+                Err(x) => state = x
+            }
+        }
+    }
+
+    /// Attempts to acquire a write lock, returning ```true``` if it's successful, and ```false``` otherwise
+    #[inline]
+    pub fn try_write (&self) -> bool {
+        if !self.mutex.try_lock_raw() { return false }
+        if self.state.compare_exchange(0, WRITER, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+            return true
+        }
+
+        unsafe { self.mutex.unlock_raw() }
+        false
+    }
+
+    /// Blocks the current thread until a read lock is acquired
+    #[cfg(feature = "sync")]
+    #[inline]
+    pub fn read_blocking (&self) {
+        if self.try_read() { return; }
+
+        let parker = Parker::current();
+        let node = core::pin::pin!(Node::new());
+
+        loop {
+            self.no_writer.register_sync(node.as_ref(), &parker);
+            if self.try_read() { break; }
+            parker.park();
+        }
+
+        self.no_writer.remove(node.as_ref());
+    }
+
+    /// Blocks the current thread until a write lock is acquired
+    #[cfg(feature = "sync")]
+    #[inline]
+    pub fn write_blocking (&self) {
+        self.mutex.lock_blocking_raw();
+        if self.start_write() { return; }
+
+        let parker = Parker::current();
+        let node = core::pin::pin!(Node::new());
+
+        loop {
+            self.no_readers.register_sync(node.as_ref(), &parker);
+            if self.state.load(Ordering::Acquire) == WRITER { break; }
+            parker.park();
+        }
+
+        self.no_readers.remove(node.as_ref());
+    }
+
+    /// Returns a future that resolves when a read lock is acquired
+    #[inline(always)]
+    pub fn read (&self) -> MovableRwLockReadFuture<'_> {
+        MovableRwLockReadFuture {
+            lock: Some(self),
+            node: Node::new()
+        }
+    }
+
+    /// Returns a future that resolves when a write lock is acquired
+    #[inline(always)]
+    pub fn write (&self) -> MovableRwLockWriteFuture<'_> {
+        MovableRwLockWriteFuture {
+            lock: Some(self),
+            node: Node::new(),
+            locked: false
+        }
+    }
+
+    /// Releases a read lock, without checking if this thread was one of it's owners
+    ///
+    /// # Safety
+    /// The lock must be held for reading, and the caller must be one of the readers
+    #[inline(always)]
+    pub unsafe fn unlock_read (&self) {
+        let prev = self.state.fetch_sub(ONE_READER, Ordering::AcqRel);
+        #[cfg(debug_assertions)]
+        assert!(prev >= ONE_READER);
+
+        // we were the last reader, and a writer is waiting for us
+        if prev == ONE_READER | WRITER {
+            self.no_readers.wake()
+        }
+    }
+
+    /// Releases a write lock, without checking if this thread was it's owner
+    ///
+    /// # Safety
+    /// The lock must be held for writing, and the caller must be the writer
+    #[inline(always)]
+    pub unsafe fn unlock_write (&self) {
+        #[cfg(debug_assertions)]
+        assert_eq!(self.state.load(Ordering::Relaxed), WRITER);
+        self.release_write()
+    }
+
+    /// Atomically turns a write lock into a read lock, letting other readers in
+    ///
+    /// # Safety
+    /// The lock must be held for writing, and the caller must be the writer
+    #[inline(always)]
+    pub unsafe fn downgrade (&self) {
+        // nobody else can touch the state while the writer bit is set
+        #[cfg(any(debug_assertions, loom))]
+        assert_eq!(self.state.swap(ONE_READER, Ordering::Release), WRITER);
+        #[cfg(not(any(debug_assertions, loom)))]
+        self.state.store(ONE_READER, Ordering::Release);
+
+        self.no_writer.wake_all();
+        self.mutex.unlock_raw();
+    }
+
+    /// Clears the writer bit and gives back the writer mutex. Readers may still be around if the writer gave up waiting for them.
+    #[inline(always)]
+    unsafe fn release_write (&self) {
+        self.state.fetch_and(!WRITER, Ordering::Release);
+        self.no_writer.wake_all();
+        self.mutex.unlock_raw();
+    }
+
+    /// Sets the writer bit once the writer mutex is held, returning ```true``` if there were no readers
+    #[inline(always)]
+    fn start_write (&self) -> bool {
+        self.state.fetch_or(WRITER, Ordering::Acquire) == 0
+    }
+
+    /// Attempts to acquire a read lock, queueing the task's waker on ```node``` if a writer holds it
+    #[inline]
+    pub(crate) fn poll_read (&self, node: Pin<&Node>, cx: &mut Context<'_>) -> Poll<()> {
+        if !self.try_read() {
+            self.no_writer.register(node, cx.waker());
+            // the writer may have left before we were queued, in which case nobody will wake us
+            if !self.try_read() {
+                return Poll::Pending;
+            }
+        }
+
+        self.no_writer.remove(node);
+        Poll::Ready(())
+    }
+
+    /// Attempts to acquire a write lock. ```locked``` tracks whether the writer mutex has already been acquired,
+    /// and ```node``` is the waiter's node on whichever queue it's currently waiting on.
+    #[inline]
+    pub(crate) fn poll_write (&self, node: Pin<&Node>, locked: &mut bool, cx: &mut Context<'_>) -> Poll<()> {
+        if !*locked {
+            if self.mutex.poll_lock(node, cx).is_pending() {
+                return Poll::Pending;
+            }
+
+            *locked = true;
+            if self.start_write() {
+                return Poll::Ready(());
+            }
+        }
+
+        if self.state.load(Ordering::Acquire) != WRITER {
+            self.no_readers.register(node, cx.waker());
+            // the last reader may have left before we were queued, in which case nobody will wake us
+            if self.state.load(Ordering::Acquire) != WRITER {
+                return Poll::Pending;
+            }
+        }
+
+        self.no_readers.remove(node);
+        Poll::Ready(())
+    }
+
+    /// Removes a pending reader from the queue
+    #[inline]
+    pub(crate) fn cancel_read (&self, node: Pin<&Node>) {
+        self.no_writer.remove(node);
+    }
+
+    /// Removes a pending writer from whichever queue it's waiting on, giving back the writer mutex if it had already acquired it
+    #[inline]
+    pub(crate) fn cancel_write (&self, node: Pin<&Node>, locked: bool) {
+        if !locked {
+            return self.mutex.cancel(node);
+        }
+
+        self.no_readers.remove(node);
+        unsafe { self.release_write() }
+    }
+}
+
+impl Default for MovableRwLock {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for MovableRwLock {
+    #[inline(always)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let state = self.state.load(Ordering::Relaxed);
+        f.debug_struct("MovableRwLock")
+            .field("readers", &(state / ONE_READER))
+            .field("writer", &(state & WRITER != 0))
+            .finish()
+    }
+}
+
+/// Future of [```read```](MovableRwLock::read)
+pub struct MovableRwLockReadFuture<'a> {
+    lock: Option<&'a MovableRwLock>,
+    node: Node
+}
+
+/// Future of [```write```](MovableRwLock::write)
+pub struct MovableRwLockWriteFuture<'a> {
+    lock: Option<&'a MovableRwLock>,
+    node: Node,
+    locked: bool
+}
+
+impl<'a> Future for MovableRwLockReadFuture<'a> {
+    type Output = ();
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let lock = if let Some(lock) = this.lock { lock } else { panic!("RwLock future already consumed") };
+        if lock.poll_read(unsafe { Pin::new_unchecked(&this.node) }, cx).is_ready() {
+            this.lock = None;
+            return Poll::Ready(());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<'a> Future for MovableRwLockWriteFuture<'a> {
+    type Output = ();
+
+    #[inline(always)]
+    fn poll(self: core::pin::Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let lock = if let Some(lock) = this.lock { lock } else { panic!("RwLock future already consumed") };
+        if lock.poll_write(unsafe { Pin::new_unchecked(&this.node) }, &mut this.locked, cx).is_ready() {
+            this.lock = None;
+            return Poll::Ready(());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<'a> FusedFuture for MovableRwLockReadFuture<'a> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.lock.is_none()
+    }
+}
+
+impl<'a> FusedFuture for MovableRwLockWriteFuture<'a> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.lock.is_none()
+    }
+}
+
+impl<'a> Drop for MovableRwLockReadFuture<'a> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(lock) = self.lock {
+            lock.cancel_read(unsafe { Pin::new_unchecked(&self.node) })
+        }
+    }
+}
+
+impl<'a> Drop for MovableRwLockWriteFuture<'a> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let Some(lock) = self.lock {
+            lock.cancel_write(unsafe { Pin::new_unchecked(&self.node) }, self.locked)
+        }
+    }
+}
@@ -0,0 +1,197 @@
+cfg_if::cfg_if! {
+    if #[cfg(feature = "checked")] {
+        extern crate std;
+
+        use core::{sync::atomic::{AtomicUsize, Ordering}, fmt::Display};
+        use std::{sync::{Mutex, PoisonError}, thread::{self, Thread}};
+
+        static NEXT_TOKEN : AtomicUsize = AtomicUsize::new(1);
+
+        /// Whoever acquired or released a mutex
+        enum Party {
+            Thread (Thread),
+            /// A lock token that was acquired asynchronously, along with the thread that last handled it
+            Task (usize, Thread)
+        }
+
+        enum State {
+            /// Last released by the party, if it was ever locked
+            Unlocked (Option<Party>),
+            /// Created with [```MovableMutex::locked```](crate::movable::MovableMutex::locked), and nobody acquired it since
+            Created,
+            /// Held by the party's lock token
+            Held (Party),
+            /// Held by the party that forgot it's lock token, which may unlock it by hand
+            Forgotten (Party)
+        }
+
+        /// Records who owns a [```MovableMutex```](crate::movable::MovableMutex), so that unlocking it by hand can be checked
+        pub(crate) struct Owner {
+            state: Mutex<State>
+        }
+
+        impl Party {
+            #[inline]
+            fn current (task: bool) -> Self {
+                match task {
+                    true => Self::Task(NEXT_TOKEN.fetch_add(1, Ordering::Relaxed), thread::current()),
+                    false => Self::Thread(thread::current())
+                }
+            }
+
+            /// The same party, but on the current thread. A task's lock token keeps it's number.
+            #[inline]
+            fn moved (&self) -> Self {
+                match self {
+                    Self::Thread(_) => Self::Thread(thread::current()),
+                    Self::Task(token, _) => Self::Task(*token, thread::current())
+                }
+            }
+
+            /// Returns ```true``` if the party is (or was last seen on) the current thread
+            #[inline]
+            fn is_current (&self) -> bool {
+                let (Self::Thread(t) | Self::Task(_, t)) = self;
+                t.id() == thread::current().id()
+            }
+        }
+
+        impl Display for Party {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                fn thread (thread: &Thread, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    write!(f, "thread '{}' ({:?})", thread.name().unwrap_or("<unnamed>"), thread.id())
+                }
+
+                match self {
+                    Self::Thread(t) => thread(t, f),
+                    Self::Task(token, t) => {
+                        write!(f, "lock token #{token} (of a task on ")?;
+                        thread(t, f)?;
+                        f.write_str(")")
+                    }
+                }
+            }
+        }
+
+        impl Owner {
+            #[inline(always)]
+            pub const fn new () -> Self {
+                Self { state: Mutex::new(State::Unlocked(None)) }
+            }
+
+            #[inline(always)]
+            pub const fn locked () -> Self {
+                Self { state: Mutex::new(State::Created) }
+            }
+
+            /// The mutex was acquired by the current thread, or by a task polled on it
+            #[inline]
+            pub fn acquired (&self, task: bool) {
+                *self.state() = State::Held(Party::current(task))
+            }
+
+            /// The mutex is about to be unlocked, either by it's lock token or by the crate
+            #[inline]
+            pub fn released (&self, name: Option<&str>) {
+                let mut state = self.state();
+                match *state {
+                    State::Held(_) | State::Forgotten(_) => *state = State::Unlocked(Some(Party::current(false))),
+                    _ => fail(name, &state)
+                }
+            }
+
+            /// The mutex is about to be unlocked by hand, which is only allowed on the thread that forgot it's lock token
+            #[inline]
+            pub fn unlocked_by_hand (&self, name: Option<&str>) {
+                let state = self.state();
+                match *state {
+                    State::Forgotten(ref party) if party.is_current() => {},
+                    _ => fail(name, &state)
+                }
+            }
+
+            /// The mutex's lock token was forgotten by the current thread
+            #[inline]
+            pub fn forgotten (&self) {
+                let mut state = self.state();
+                if let State::Held(ref party) = *state {
+                    *state = State::Forgotten(party.moved())
+                }
+            }
+
+            /// A lock token was made for a mutex that was locked without one, or whose token was forgotten by the current thread.
+            /// A task's forgotten token is made again with the same number.
+            #[inline]
+            pub fn claimed (&self, name: Option<&str>) {
+                let mut state = self.state();
+                match *state {
+                    State::Created => *state = State::Held(Party::current(false)),
+                    State::Forgotten(ref party) if party.is_current() => *state = State::Held(party.moved()),
+                    State::Forgotten(ref party) => panic!("{} made a lock token for {}, but it's token was forgotten by {party}", Party::current(false), Label(name)),
+                    State::Held(ref owner) => panic!("{} made a lock token for {}, but it's held by {owner}'s lock token", Party::current(false), Label(name)),
+                    State::Unlocked(_) => panic!("{} made a lock token for {}, but it isn't locked", Party::current(false), Label(name))
+                }
+            }
+
+            #[inline(always)]
+            fn state (&self) -> std::sync::MutexGuard<'_, State> {
+                self.state.lock().unwrap_or_else(PoisonError::into_inner)
+            }
+        }
+
+        /// Panics because the current thread unlocked the mutex while it was in ```state```
+        #[cold]
+        fn fail (name: Option<&str>, state: &State) -> ! {
+            let current = Party::current(false);
+            let mutex = Label(name);
+            match state {
+                State::Unlocked(Some(prev)) => panic!("{current} unlocked {mutex}, but it had already been unlocked by {prev}"),
+                State::Unlocked(None) => panic!("{current} unlocked {mutex}, but it was never locked"),
+                State::Created => panic!("{current} unlocked {mutex}, but it was created locked and nobody has acquired it"),
+                State::Held(owner) => panic!("{current} unlocked {mutex} by hand, but it's held by {owner}'s lock token"),
+                State::Forgotten(owner) => panic!("{current} unlocked {mutex} by hand, but it's lock token was forgotten by {owner}")
+            }
+        }
+
+        struct Label<'a> (Option<&'a str>);
+
+        impl Display for Label<'_> {
+            #[inline]
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self.0 {
+                    Some(name) => write!(f, "mutex '{name}'"),
+                    None => f.write_str("an unnamed mutex")
+                }
+            }
+        }
+    } else {
+        pub(crate) struct Owner;
+
+        impl Owner {
+            #[inline(always)]
+            pub const fn new () -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub const fn locked () -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn acquired (&self, _: bool) {}
+
+            #[inline(always)]
+            pub fn released (&self, _: Option<&str>) {}
+
+            #[inline(always)]
+            pub fn unlocked_by_hand (&self, _: Option<&str>) {}
+
+            #[inline(always)]
+            pub fn forgotten (&self) {}
+
+            #[inline(always)]
+            pub fn claimed (&self, _: Option<&str>) {}
+        }
+    }
+}
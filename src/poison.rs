@@ -0,0 +1,172 @@
+cfg_if::cfg_if! {
+    if #[cfg(feature = "poison")] {
+        extern crate std;
+        use core::{sync::atomic::Ordering, fmt::{Debug, Display}};
+        use crate::{TRUE, FALSE};
+
+        /// Records whether a thread panicked while holding a lock
+        pub(crate) struct Flag (crate::Flag);
+
+        /// Whether the thread was already panicking when the lock was acquired
+        #[derive(Clone, Copy)]
+        pub(crate) struct Guard {
+            panicking: bool
+        }
+
+        impl Flag {
+            loom_const_fn! {
+                #[inline(always)]
+                pub fn new () -> Self {
+                    Self(crate::Flag::new(FALSE))
+                }
+            }
+
+            #[inline(always)]
+            pub fn get (&self) -> bool {
+                self.0.load(Ordering::Relaxed) == TRUE
+            }
+
+            #[inline(always)]
+            pub fn clear (&self) {
+                self.0.store(FALSE, Ordering::Relaxed)
+            }
+
+            #[inline(always)]
+            pub fn guard (&self) -> Guard {
+                Guard { panicking: std::thread::panicking() }
+            }
+
+            /// Poisons the lock if the thread started panicking while it was held
+            #[inline(always)]
+            pub fn done (&self, guard: &Guard) {
+                if !guard.panicking && std::thread::panicking() {
+                    self.0.store(TRUE, Ordering::Relaxed)
+                }
+            }
+
+        }
+
+        /// Wraps a newly acquired guard for the ```_poisoned``` lock methods, depending on whether the lock was poisoned
+        #[inline(always)]
+        pub(crate) fn locked<G> (poisoned: bool, guard: G) -> LockResult<G> {
+            match poisoned {
+                true => Err(PoisonError::new(guard)),
+                false => Ok(guard)
+            }
+        }
+
+        #[inline(always)]
+        pub(crate) fn try_locked<G> (guard: Option<G>, poisoned: impl FnOnce(&G) -> bool) -> TryLockResult<G> {
+            match guard {
+                Some(guard) => locked(poisoned(&guard), guard).map_err(TryLockError::Poisoned),
+                None => Err(TryLockError::WouldBlock)
+            }
+        }
+
+        /// Error returned when a lock is acquired after another thread panicked while holding it.
+        /// The guard can still be recovered with [```into_inner```](PoisonError::into_inner).
+        pub struct PoisonError<G> {
+            guard: G
+        }
+
+        impl<G> PoisonError<G> {
+            #[inline(always)]
+            pub fn new (guard: G) -> Self {
+                Self { guard }
+            }
+
+            /// Consumes the error, returning the guard
+            #[inline(always)]
+            pub fn into_inner (self) -> G {
+                self.guard
+            }
+
+            #[inline(always)]
+            pub fn get_ref (&self) -> &G {
+                &self.guard
+            }
+
+            #[inline(always)]
+            pub fn get_mut (&mut self) -> &mut G {
+                &mut self.guard
+            }
+        }
+
+        impl<G> Debug for PoisonError<G> {
+            #[inline(always)]
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_struct("PoisonError").finish_non_exhaustive()
+            }
+        }
+
+        impl<G> Display for PoisonError<G> {
+            #[inline(always)]
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str("poisoned lock: another task failed inside")
+            }
+        }
+
+        impl<G> std::error::Error for PoisonError<G> {}
+
+        /// Error returned by the ```try_lock``` family of methods
+        pub enum TryLockError<G> {
+            /// The lock was acquired, but it's poisoned
+            Poisoned (PoisonError<G>),
+            /// The lock is held by someone else, and (for the ```timeout``` methods) it wasn't released in time
+            WouldBlock
+        }
+
+        impl<G> From<PoisonError<G>> for TryLockError<G> {
+            #[inline(always)]
+            fn from(x: PoisonError<G>) -> Self {
+                Self::Poisoned(x)
+            }
+        }
+
+        impl<G> Debug for TryLockError<G> {
+            #[inline(always)]
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self {
+                    Self::Poisoned (e) => f.debug_tuple("Poisoned").field(e).finish(),
+                    Self::WouldBlock => f.write_str("WouldBlock")
+                }
+            }
+        }
+
+        impl<G> Display for TryLockError<G> {
+            #[inline(always)]
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self {
+                    Self::Poisoned (e) => Display::fmt(e, f),
+                    Self::WouldBlock => f.write_str("try_lock failed because the operation would block")
+                }
+            }
+        }
+
+        impl<G> std::error::Error for TryLockError<G> {}
+
+        pub type LockResult<G> = Result<G, PoisonError<G>>;
+        pub type TryLockResult<G> = Result<G, TryLockError<G>>;
+    } else {
+        /// Without the ```poison``` feature, locks are never poisoned
+        pub(crate) struct Flag;
+
+        #[derive(Clone, Copy)]
+        pub(crate) struct Guard;
+
+        impl Flag {
+            #[inline(always)]
+            pub const fn new () -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn guard (&self) -> Guard {
+                Guard
+            }
+
+            #[inline(always)]
+            pub fn done (&self, _guard: &Guard) {}
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{Mutex, RwLock};
+
+/// A pointer that always dereferences to the same place, even after it's moved.
+///
+/// Owned guards (like [```PtrMutexGuard```](crate::guards::PtrMutexGuard)) hold one of these instead of a reference,
+/// and unlock whatever it points to when they're dropped. It's the [```stable_deref_trait```](https://docs.rs/stable_deref_trait) crate's trait,
+/// so pointers from other crates (like ```triomphe::Arc```) can be used as they are.
+///
+/// ```Pin<P>``` isn't one, since that crate doesn't implement it for ```Pin```. A mutex is ```Unpin``` whenever it's data is,
+/// so a pinned pointer to one can be unwrapped with ```Pin::into_inner``` and locked through that:
+///
+/// ```
+/// # use std::{pin::Pin, sync::Arc};
+/// # use async_mutex::Mutex;
+/// let mutex = Arc::pin(Mutex::new(0));
+/// *Mutex::lock_blocking_ptr(Pin::into_inner(mutex)) += 1;
+/// ```
+pub use stable_deref_trait::StableDeref;
+
+/// A [```StableDeref```] pointer whose clones point to the same place as it does, like ```Rc``` and ```Arc``` (but not ```Box```).
+/// [```Mutex::poll_lock_ptr```] needs one, since the guard and the waiter hold clones of the pointer the mutex was locked through.
+pub use stable_deref_trait::CloneStableDeref;
+
+/// A [```StableDeref```] pointer to a [```Mutex```], which can be locked with [```Mutex::lock_ptr```].
+/// It's implemented for every such pointer.
+pub trait MutexPtr: StableDeref<Target = Mutex<<Self as MutexPtr>::Data>> {
+    /// Data protected by the mutex
+    type Data: ?Sized;
+}
+
+impl<T: ?Sized, P: StableDeref<Target = Mutex<T>>> MutexPtr for P {
+    type Data = T;
+}
+
+/// A [```StableDeref```] pointer to a [```RwLock```], which can be locked with [```RwLock::read_ptr```] and [```RwLock::write_ptr```].
+/// It's implemented for every such pointer.
+pub trait RwLockPtr: StableDeref<Target = RwLock<<Self as RwLockPtr>::Data>> {
+    /// Data protected by the lock
+    type Data: ?Sized;
+}
+
+impl<T: ?Sized, P: StableDeref<Target = RwLock<T>>> RwLockPtr for P {
+    type Data = T;
+}
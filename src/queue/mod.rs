@@ -1,55 +1,387 @@
+#[cfg(feature = "sync")]
 extern crate alloc;
 
-use core::{sync::atomic::Ordering};
-use alloc::collections::VecDeque;
+use core::{cell::Cell, marker::PhantomPinned, panic::{UnwindSafe, RefUnwindSafe}, pin::Pin, ptr};
+use crate::shim::{atomic::{AtomicUsize, Ordering}, UnsafeCell};
+#[cfg(not(loom))]
+use crate::shim::atomic::fence;
 use crate::{waker::Waker, Flag, FALSE, TRUE};
-flat_mod!(cell);
+flat_mod!(wakers);
 
+/// Intrusive list of waiters. The nodes live inside the (pinned) futures and blocking calls that wait on the queue,
+/// so queueing a waiter never allocates.
+///
+/// It isn't lock-free: the list is guarded by a spin lock, held for the few pointer updates of each push, removal or wakeup.
+/// Only checking if it's empty (which an uncontended unlock does through the mutex's own state) skips it.
 pub struct Queue {
     locked: Flag,
-    queue: OnceCell<VecDeque<Waker>>
+    /// Number of nodes on the list, so that an empty queue can be checked without locking it
+    len: AtomicUsize,
+    list: UnsafeCell<List>
+}
+
+struct List {
+    head: *const Node,
+    tail: *const Node
+}
+
+/// The queue's list, borrowed while it's locked
+pub struct Entries<'a> {
+    list: &'a mut List,
+    len: &'a AtomicUsize
+}
+
+/// A waiter's slot in the queue. It must be removed from the queue before it's moved or dropped,
+/// which pinning (and the waiters' destructors) takes care of.
+pub struct Node {
+    /// Only ever accessed by the node's owner
+    queued: Cell<bool>,
+    /// When the waiter started waiting, only ever accessed by the node's owner
+    pub wait: crate::clock::Stamp,
+    /// Only ever accessed while the queue is locked
+    state: UnsafeCell<State>,
+    _pin: PhantomPinned
+}
+
+struct State {
+    prev: *const Node,
+    next: *const Node,
+    /// ```None``` means the waiter has already been woken
+    waker: Option<Waker>,
+    /// How much of the resource the waiter is asking for
+    weight: usize,
+    /// Ownership has been handed straight to this waiter
+    handoff: bool,
+    /// The waiter has been woken before, but someone else got to the lock first
+    barged: bool
+}
+
+/// State of a waiter's slot at the moment it's removed from the queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitState {
+    Waiting,
+    Woken,
+    HandedOff
 }
 
 impl Queue {
+    loom_const_fn! {
+        #[inline(always)]
+        pub fn new () -> Self {
+            Self {
+                locked: Flag::new(FALSE),
+                len: AtomicUsize::new(0),
+                list: UnsafeCell::new(List { head: ptr::null(), tail: ptr::null() })
+            }
+        }
+    }
+
+    /// Returns ```true``` if there are no waiters on the queue, without locking it.
+    ///
+    /// Waiters re-check the resource they wait on after being queued, and whoever releases it checks the queue
+    /// after doing so, so that at least one of them sees the other.
     #[inline(always)]
-    pub const fn new () -> Self {
-        Self {
-            locked: Flag::new(FALSE),
-            queue: OnceCell::new()
+    pub fn is_empty (&self) -> bool {
+        #[cfg(not(loom))]
+        {
+            fence(Ordering::SeqCst);
+            self.len.load(Ordering::Relaxed) == 0
         }
+        // loom treats ```SeqCst``` as ```AcqRel```, so it's modeled with read-modify-writes on ```len``` instead
+        #[cfg(loom)]
+        { self.len.fetch_add(0, Ordering::AcqRel) == 0 }
+    }
+
+    /// Returns the number of waiters on the queue, without locking it
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn len (&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Queues ```node``` with ```waker```. If it's already queued, it's waker is replaced in place,
+    /// keeping it's position on the queue.
+    ///
+    /// If ownership had been handed to the node, it's removed instead and ```true``` is returned.
+    #[inline(always)]
+    pub fn register (&self, node: Pin<&Node>, waker: &core::task::Waker) -> bool {
+        self.with(|queue| queue.register(node, waker))
+    }
+
+    /// Queues a blocking waiter's parker, rearming it if it has already been woken
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn register_sync (&self, node: Pin<&Node>, parker: &alloc::sync::Arc<crate::waker::Parker>) -> bool {
+        self.with(|queue| queue.register_sync(node, parker))
+    }
+
+    /// Removes ```node``` from the queue, returning the state it was in, or ```None``` if it wasn't queued
+    #[inline(always)]
+    pub fn remove (&self, node: Pin<&Node>) -> Option<WaitState> {
+        if !node.is_queued() { return None }
+        Some(self.with(|queue| queue.remove(node)))
     }
 
+    /// Runs ```f``` while the queue is locked, for waiters that need to check their own state atomically with the queue's
     #[inline(always)]
-    pub fn push (&self, v: Waker) {
-        let queue = self.lock();
-        queue.push_back(v);
+    pub fn with<R, F: FnOnce(&mut Entries<'_>) -> R> (&self, f: F) -> R {
+        let result = f(&mut self.lock());
         self.unlock();
+        result
     }
 
+    /// Wakes every waiter on the queue that hasn't already been woken
+    #[inline]
+    pub fn wake_all (&self) {
+        if self.is_empty() { return }
+
+        loop {
+            let mut wakers = WakeList::new();
+            self.with(|queue| {
+                for waker in queue.iter().filter_map(|x| x.waker.take()) {
+                    wakers.push(waker);
+                    if wakers.is_full() { break }
+                }
+            });
+
+            let full = wakers.is_full();
+            wakers.wake();
+            if !full { break }
+        }
+    }
+
+    /// Wakes the waiter at the front of the queue, unless it has already been woken
     #[inline(always)]
     pub fn wake (&self) {
-        let queue = self.lock();
-        if let Some(waker) = queue.pop_front() {
+        if self.is_empty() { return }
+        let waker = self.with(|queue| queue.front().and_then(|entry| entry.waker.take()));
+
+        if let Some(waker) = waker {
             waker.wake()
         }
-        self.unlock();
     }
 
     #[inline(always)]
-    fn lock (&self) -> &mut VecDeque<Waker> {
-        while self.locked.compare_exchange(FALSE, TRUE, Ordering::AcqRel, Ordering::Acquire).is_err() { core::hint::spin_loop() }
-        let queue = self.queue.get_or_default() as *const VecDeque<Waker>;
-        unsafe { &mut *(queue as *mut VecDeque<Waker>) }
+    fn lock (&self) -> Entries<'_> {
+        while self.locked.compare_exchange(FALSE, TRUE, Ordering::AcqRel, Ordering::Acquire).is_err() { crate::shim::spin_loop() }
+        Entries { list: self.list.with_mut(|list| unsafe { &mut *list }), len: &self.len }
     }
 
     #[inline(always)]
     fn unlock (&self) {
-        #[cfg(debug_assertions)]
+        #[cfg(any(debug_assertions, loom))]
         assert_eq!(TRUE, self.locked.swap(FALSE, Ordering::Release));
-        #[cfg(not(debug_assertions))]
+        #[cfg(not(any(debug_assertions, loom)))]
         self.locked.store(FALSE, Ordering::Release);
     }
 }
 
+impl<'a> Entries<'a> {
+    /// Returns ```true``` if there are no nodes on the queue
+    #[inline(always)]
+    pub fn is_empty (&self) -> bool {
+        self.list.head.is_null()
+    }
+
+    /// Returns ```true``` if there are waiters on the queue that haven't been handed ownership yet
+    #[inline(always)]
+    pub fn has_waiters (&mut self) -> bool {
+        self.iter().any(|x| !x.handoff)
+    }
+
+    /// Like [```Queue::register```], for a queue that's already locked
+    #[allow(clippy::useless_conversion)]
+    #[inline(always)]
+    pub fn register (&mut self, node: Pin<&Node>, waker: &core::task::Waker) -> bool {
+        self.register_with(node, |w| w.will_wake(waker), || waker.clone().into())
+    }
+
+    /// Like [```Queue::register_sync```], for a queue that's already locked
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn register_sync (&mut self, node: Pin<&Node>, parker: &alloc::sync::Arc<crate::waker::Parker>) -> bool {
+        self.register_with(node, |_| true, || Waker::Sync(parker.clone()))
+    }
+
+    /// Wakes the waiter at the front of the queue (if any) as it's resource is released. If ```handoff``` returns ```true```
+    /// (it's told whether the waiter has already been barged past), ownership is handed straight to it.
+    ///
+    /// Returns the waiter's waker, to be woken once the queue is unlocked, and whether ownership was handed off.
+    #[inline]
+    pub fn release<F: FnOnce(bool) -> bool> (&mut self, handoff: F) -> (Option<Waker>, bool) {
+        match self.front() {
+            Some(entry) if handoff(entry.barged) => {
+                entry.handoff = true;
+                (entry.waker.take(), true)
+            },
+            Some(entry) => (entry.waker.take(), false),
+            None => (None, false)
+        }
+    }
+
+    /// Pushes ```node``` to the back of the queue
+    #[inline]
+    pub fn push (&mut self, node: Pin<&Node>, waker: Waker, weight: usize) {
+        debug_assert!(!node.is_queued());
+        let entry = unsafe { node.state() };
+        *entry = State { prev: self.list.tail, next: ptr::null(), waker: Some(waker), weight, handoff: false, barged: false };
+
+        let node = &*node as *const Node;
+        match unsafe { self.list.tail.as_ref() } {
+            Some(tail) => unsafe { tail.state().next = node },
+            None => self.list.head = node
+        }
+
+        self.list.tail = node;
+        unsafe { (*node).queued.set(true) }
+        // pairs with the fence in [```Queue::is_empty```]
+        #[cfg(not(loom))]
+        {
+            self.len.fetch_add(1, Ordering::Relaxed);
+            fence(Ordering::SeqCst);
+        }
+        #[cfg(loom)]
+        self.len.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Removes ```node``` from the queue, returning the state it was in
+    #[inline]
+    pub fn remove (&mut self, node: Pin<&Node>) -> WaitState {
+        let entry = unsafe { node.state() };
+        let state = match (entry.handoff, &entry.waker) {
+            (true, _) => WaitState::HandedOff,
+            (false, None) => WaitState::Woken,
+            (false, Some(_)) => WaitState::Waiting
+        };
+
+        entry.waker = None;
+        self.unlink(node);
+        state
+    }
+
+    /// Hands ownership to the waiters at the front of the queue for as long as ```f``` accepts their weight,
+    /// adding their wakers to ```wakers```. Returns ```true``` if it stopped because ```wakers``` is full.
+    #[inline]
+    pub fn handoff_while<F: FnMut(usize) -> bool> (&mut self, wakers: &mut WakeList, mut f: F) -> bool {
+        for entry in self.iter().filter(|x| !x.handoff) {
+            if wakers.is_full() { return true }
+            if !f(entry.weight) { break }
+            entry.handoff = true;
+            if let Some(waker) = entry.waker.take() {
+                wakers.push(waker)
+            }
+        }
+        false
+    }
+
+    #[inline]
+    fn register_with<W: FnOnce(&Waker) -> bool, F: FnOnce() -> Waker> (&mut self, node: Pin<&Node>, will_wake: W, f: F) -> bool {
+        if !node.is_queued() {
+            self.push(node, f(), 1);
+            return false
+        }
+
+        let entry = unsafe { node.state() };
+        if entry.handoff {
+            self.unlink(node);
+            return true;
+        }
+
+        match entry.waker {
+            Some(ref waker) if will_wake(waker) => {},
+            Some(_) => entry.waker = Some(f()),
+            None => {
+                entry.barged = true;
+                entry.waker = Some(f())
+            }
+        }
+
+        false
+    }
+
+    #[inline(always)]
+    fn front (&mut self) -> Option<&mut State> {
+        unsafe { self.list.head.as_ref().map(|x| x.state()) }
+    }
+
+    #[inline(always)]
+    fn iter (&mut self) -> impl Iterator<Item = &mut State> {
+        let mut node = self.list.head;
+        core::iter::from_fn(move || {
+            let entry = unsafe { node.as_ref()?.state() };
+            node = entry.next;
+            Some(entry)
+        })
+    }
+
+    #[inline]
+    fn unlink (&mut self, node: Pin<&Node>) {
+        debug_assert!(node.is_queued());
+        let entry = unsafe { node.state() };
+
+        match unsafe { entry.prev.as_ref() } {
+            Some(prev) => unsafe { prev.state().next = entry.next },
+            None => self.list.head = entry.next
+        }
+
+        match unsafe { entry.next.as_ref() } {
+            Some(next) => unsafe { next.state().prev = entry.prev },
+            None => self.list.tail = entry.prev
+        }
+
+        entry.prev = ptr::null();
+        entry.next = ptr::null();
+        node.queued.set(false);
+        self.len.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Node {
+    loom_const_fn! {
+        #[inline(always)]
+        pub fn new () -> Self {
+            Self {
+                queued: Cell::new(false),
+                wait: crate::clock::Stamp::new(),
+                state: UnsafeCell::new(State { prev: ptr::null(), next: ptr::null(), waker: None, weight: 0, handoff: false, barged: false }),
+                _pin: PhantomPinned
+            }
+        }
+    }
+
+    /// Returns ```true``` if the node is currently on a queue. Must only be called by the node's owner.
+    #[inline(always)]
+    pub fn is_queued (&self) -> bool {
+        self.queued.get()
+    }
+
+    /// # Safety
+    /// The queue the node is on must be locked
+    #[allow(clippy::mut_from_ref)]
+    #[inline(always)]
+    unsafe fn state (&self) -> &mut State {
+        self.state.with_mut(|state| &mut *state)
+    }
+}
+
+impl Default for Node {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Node {
+    #[inline(always)]
+    fn drop(&mut self) {
+        debug_assert!(!self.is_queued(), "waiter dropped while still on the queue");
+    }
+}
+
 unsafe impl Send for Queue {}
-unsafe impl Sync for Queue {}
\ No newline at end of file
+unsafe impl Sync for Queue {}
+// the list is only changed while the queue is locked, by code that can't panic halfway through
+impl UnwindSafe for Queue {}
+impl RefUnwindSafe for Queue {}
+unsafe impl Send for Node {}
+unsafe impl Sync for Node {}
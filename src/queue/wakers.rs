@@ -0,0 +1,46 @@
+use crate::waker::Waker;
+
+const CAPACITY : usize = 32;
+
+/// Wakers collected while a queue is locked, to be woken once it's unlocked.
+/// It has a fixed capacity so that it doesn't have to allocate, so waking many waiters is done in batches.
+pub struct WakeList {
+    wakers: [Option<Waker>; CAPACITY],
+    len: usize
+}
+
+impl WakeList {
+    #[inline(always)]
+    pub const fn new () -> Self {
+        Self {
+            wakers: [const { None }; CAPACITY],
+            len: 0
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_full (&self) -> bool {
+        self.len == CAPACITY
+    }
+
+    #[inline(always)]
+    pub fn push (&mut self, waker: Waker) {
+        debug_assert!(!self.is_full());
+        self.wakers[self.len] = Some(waker);
+        self.len += 1;
+    }
+
+    #[inline]
+    pub fn wake (mut self) {
+        for waker in self.wakers[..self.len].iter_mut().filter_map(Option::take) {
+            waker.wake()
+        }
+    }
+}
+
+impl Default for WakeList {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,212 @@
+#[cfg(feature = "std")]
+extern crate std;
+
+use core::{fmt::Debug, num::NonZeroUsize, marker::PhantomData};
+use crate::{guards::*, movable::{MovableMutex, Fairness}, shim::{atomic::{AtomicUsize, Ordering}, UnsafeCell}, queue::Node};
+
+static NEXT_OWNER : core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(1);
+
+/// Identity of whoever holds a [```ReentrantMutex```].
+///
+/// Threads use one of their own when they ```lock_blocking```, while tasks bring theirs to [```lock```](ReentrantMutex::lock).
+/// A task would usually make one with [```OwnerId::new```] and keep it in it's state (or in a task-local), passing it down to whatever re-enters the mutex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OwnerId (NonZeroUsize);
+
+impl OwnerId {
+    /// Returns a new owner id, different from every other one
+    #[inline]
+    pub fn new () -> Self {
+        match NonZeroUsize::new(NEXT_OWNER.fetch_add(1, core::sync::atomic::Ordering::Relaxed)) {
+            Some(id) => Self(id),
+            None => panic!("ran out of owner ids")
+        }
+    }
+
+    /// Returns the current thread's owner id
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub(crate) fn thread () -> Self {
+        crate::shim::thread_local! {
+            static THREAD : OwnerId = OwnerId::new();
+        }
+
+        THREAD.with(|x| *x)
+    }
+}
+
+impl Default for OwnerId {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A mutex that can be locked again by it's current holder without deadlocking, attached to a value.
+/// It's unlocked once every guard of it's holder has been dropped.
+///
+/// Since the holder may have several guards at once, they only give shared access to the data.
+/// Use a ```RefCell``` (or a ```Cell```) inside to mutate it.
+pub struct ReentrantMutex<T: ?Sized> {
+    pub(crate) inner: MovableMutex,
+    /// Id of the current holder, or zero
+    pub(crate) owner: AtomicUsize,
+    /// Number of guards the current holder has. Only the holder touches it.
+    pub(crate) depth: AtomicUsize,
+    pub(crate) data: UnsafeCell<T>
+}
+
+impl<T> ReentrantMutex<T> {
+    loom_const_fn! {
+        /// Creates a new reentrant mutex
+        #[inline(always)]
+        pub fn new (data: T) -> Self {
+            Self::with_fairness(data, Fairness::Barging)
+        }
+    }
+
+    loom_const_fn! {
+        /// Creates a new reentrant mutex with the specified fairness policy
+        #[inline(always)]
+        pub fn with_fairness (data: T, fairness: Fairness) -> Self {
+            Self {
+                inner: MovableMutex::with_fairness(fairness),
+                owner: AtomicUsize::new(0),
+                depth: AtomicUsize::new(0),
+                data: UnsafeCell::new(data)
+            }
+        }
+    }
+
+    /// Gives the mutex a name, which shows up in it's ```Debug``` output and (with the ```tracing``` feature) in it's events
+    #[inline(always)]
+    pub const fn named (mut self, name: &'static str) -> Self {
+        self.inner.name = Some(name);
+        self
+    }
+
+    /// Consumes the mutex and returns its underlying data
+    #[inline(always)]
+    pub fn into_inner (self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> ReentrantMutex<T> {
+    /// Attempts to lock the mutex on behalf of the current thread, returning ```None``` if someone else holds it
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn try_lock (&self) -> Option<ReentrantMutexGuard<'_, T>> {
+        match self.try_enter(OwnerId::thread()) {
+            true => Some(ReentrantMutexGuard { mutex: self, _phantom: PhantomData }),
+            false => None
+        }
+    }
+
+    /// Blocks the current thread until the mutex is acquired, returning right away if the thread already holds it
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn lock_blocking (&self) -> ReentrantMutexGuard<'_, T> {
+        let owner = OwnerId::thread();
+        if !self.reenter(owner) {
+            self.inner.lock_blocking_raw();
+            self.entered(owner);
+        }
+
+        ReentrantMutexGuard { mutex: self, _phantom: PhantomData }
+    }
+
+    /// Attempts to lock the mutex on behalf of ```owner```, returning ```None``` if someone else holds it
+    #[inline(always)]
+    pub fn try_lock_with (&self, owner: OwnerId) -> Option<ReentrantMutexTaskGuard<'_, T>> where T: Sync {
+        match self.try_enter(owner) {
+            true => Some(ReentrantMutexTaskGuard { mutex: self, _phantom: PhantomData }),
+            false => None
+        }
+    }
+
+    /// Returns a future that locks the mutex on behalf of ```owner```, resolving right away if ```owner``` already holds it.
+    ///
+    /// An owner id must only be used by one task at a time, or the mutex's recursion depth may be miscounted.
+    /// The data must be ```Sync```, since it's tasks may run on different threads.
+    #[inline(always)]
+    pub fn lock (&self, owner: OwnerId) -> ReentrantMutexFuture<'_, T> where T: Sync {
+        ReentrantMutexFuture {
+            mutex: Some(self),
+            owner,
+            node: Node::new()
+        }
+    }
+
+    /// Returns a mutable reference to the underlying data
+    #[inline(always)]
+    pub fn get_mut (&mut self) -> &mut T {
+        self.data.with_mut(|data| unsafe { &mut *data })
+    }
+
+    /// Returns the mutex's name, if it has one
+    #[inline(always)]
+    pub fn name (&self) -> Option<&'static str> {
+        self.inner.name
+    }
+
+    #[inline(always)]
+    fn try_enter (&self, owner: OwnerId) -> bool {
+        if self.reenter(owner) { return true }
+        if self.inner.try_lock_raw() {
+            self.entered(owner);
+            return true
+        }
+
+        false
+    }
+
+    /// Adds a guard for ```owner``` if it already holds the mutex
+    #[inline]
+    pub(crate) fn reenter (&self, owner: OwnerId) -> bool {
+        // only the holder ever sees it's own id here, since it's the one that stored it
+        if self.owner.load(Ordering::Relaxed) != owner.0.get() { return false }
+        let depth = self.depth.load(Ordering::Relaxed);
+        match depth.checked_add(1) {
+            Some(depth) => self.depth.store(depth, Ordering::Relaxed),
+            None => panic!("reentrant mutex locked too many times")
+        }
+
+        true
+    }
+
+    /// ```owner``` has just acquired the mutex
+    #[inline(always)]
+    pub(crate) fn entered (&self, owner: OwnerId) {
+        self.owner.store(owner.0.get(), Ordering::Relaxed);
+        self.depth.store(1, Ordering::Relaxed);
+    }
+
+    /// Drops one of the holder's guards, unlocking the mutex if it was the last one
+    #[inline]
+    pub(crate) fn exit (&self) {
+        let depth = self.depth.load(Ordering::Relaxed) - 1;
+        self.depth.store(depth, Ordering::Relaxed);
+        if depth == 0 {
+            self.owner.store(0, Ordering::Relaxed);
+            unsafe { self.inner.unlock_raw() }
+        }
+    }
+}
+
+impl<T: ?Sized> Debug for ReentrantMutex<T> {
+    #[inline(always)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut f = f.debug_struct("ReentrantMutex");
+        if let Some(name) = self.inner.name {
+            f.field("name", &name);
+        }
+
+        f.field("locked", &self.inner.is_locked()).finish()
+    }
+}
+
+// like a regular mutex, the data is only reached by one thread at a time unless it's ```Sync```:
+// thread guards can't leave their thread, and locking on behalf of a task requires ```T: Sync```
+unsafe impl<T: ?Sized + Send> Send for ReentrantMutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for ReentrantMutex<T> {}
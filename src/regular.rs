@@ -1,34 +1,70 @@
+#[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(all(feature = "std", not(loom)))]
+extern crate std;
 
-use core::{cell::UnsafeCell, fmt::Debug};
+use core::{fmt::Debug, time::Duration, pin::Pin, task::{Context, Poll}};
+#[cfg(feature = "poison")]
+use core::panic::{UnwindSafe, RefUnwindSafe};
+#[cfg(feature = "alloc")]
 use alloc::{rc::Rc, sync::Arc};
-use crate::{guards::*, movable::MovableMutex};
+#[cfg(all(feature = "std", not(loom)))]
+use std::time::Instant;
+use crate::{StableDeref, CloneStableDeref, Timer, guards::*, movable::{MovableMutex, Fairness}, poison, queue::Node, shim::UnsafeCell};
+#[cfg(feature = "poison")]
+use crate::{LockResult, TryLockResult};
 
 /// A mutually exclusive lock, attached to a value
 pub struct Mutex<T: ?Sized> {
     pub(crate) inner: MovableMutex,
+    pub(crate) poison: poison::Flag,
     pub(crate) data: UnsafeCell<T>,
 }
 
 impl<T> Mutex<T> {
-    /// Creates a new mutex
-    #[inline(always)]
-    pub const fn new (data: T) -> Self {
-        Self {
-            inner: MovableMutex::new(),
-            data: UnsafeCell::new(data),
+    loom_const_fn! {
+        /// Creates a new mutex
+        #[inline(always)]
+        pub fn new (data: T) -> Self {
+            Self {
+                inner: MovableMutex::new(),
+                poison: poison::Flag::new(),
+                data: UnsafeCell::new(data),
+            }
         }
     }
 
-    /// Creates a new mutex from it's parts
-    #[inline(always)]
-    pub const fn from_raw_parts (mutex: MovableMutex, data: T) -> Self {
-        Self { 
-            inner: mutex,
-            data: UnsafeCell::new(data)
+    loom_const_fn! {
+        /// Creates a new mutex with the specified fairness policy
+        #[inline(always)]
+        pub fn with_fairness (data: T, fairness: Fairness) -> Self {
+            Self {
+                inner: MovableMutex::with_fairness(fairness),
+                poison: poison::Flag::new(),
+                data: UnsafeCell::new(data),
+            }
         }
     }
 
+    loom_const_fn! {
+        /// Creates a new mutex from it's parts
+        #[inline(always)]
+        pub fn from_raw_parts (mutex: MovableMutex, data: T) -> Self {
+            Self { 
+                inner: mutex,
+                poison: poison::Flag::new(),
+                data: UnsafeCell::new(data)
+            }
+        }
+    }
+
+    /// Gives the mutex a name, which shows up in it's ```Debug``` output and (with the ```tracing``` feature) in it's events
+    #[inline(always)]
+    pub const fn named (mut self, name: &'static str) -> Self {
+        self.inner.name = Some(name);
+        self
+    }
+
     /// Consumes the mutex and returns its underlying data
     #[inline(always)]
     pub fn into_inner (self) -> T {
@@ -43,102 +79,272 @@ impl<T> Mutex<T> {
 }
 
 impl<T: ?Sized> Mutex<T> {
-    /// Attempts to lock the mutex, returning a [```MutexGuard```](crate::guards::MutexGuard) if it's successful, and ```None``` otherwise
+    /// Attempts to lock the mutex, returning a [```MutexGuard```](crate::guards::MutexGuard) if it's successful, and ```None``` otherwise.
+    /// 
+    /// Lock methods don't check if the mutex is poisoned. With the ```poison``` feature enabled,
+    /// the ```_poisoned``` methods (like ```lock_poisoned```) do, and every guard has an ```is_poisoned``` method.
     #[inline(always)]
     pub fn try_lock (&self) -> Option<MutexGuard<'_, T>> {
-        if self.inner.try_lock() {
-            return Some(MutexGuard {
-                inner: self,
-            })
-        }
-
-        None
+        self.inner.try_lock_raw().then(|| MutexGuard::new(self))
     }
 
     /// Blocks the current thread until the mutex is acquired, returning a [```MutexGuard```](crate::guards::MutexGuard)
     #[cfg(feature = "sync")]
     #[inline(always)]
     pub fn lock_blocking (&self) -> MutexGuard<'_, T> {
-        self.inner.lock_blocking();
-        MutexGuard {
-            inner: self,
-        }
+        self.inner.lock_blocking_raw();
+        MutexGuard::new(self)
+    }
+
+    /// Blocks the current thread until the mutex is acquired or ```timeout``` has passed
+    #[cfg(all(feature = "std", not(loom)))]
+    #[inline(always)]
+    pub fn lock_blocking_timeout (&self, timeout: Duration) -> Option<MutexGuard<'_, T>> {
+        self.inner.lock_blocking_timeout_raw(timeout).then(|| MutexGuard::new(self))
+    }
+
+    /// Blocks the current thread until the mutex is acquired or ```deadline``` is reached
+    #[cfg(all(feature = "std", not(loom)))]
+    #[inline(always)]
+    pub fn lock_blocking_until (&self, deadline: Instant) -> Option<MutexGuard<'_, T>> {
+        self.inner.lock_blocking_until_raw(Some(deadline)).then(|| MutexGuard::new(self))
     }
 
     #[inline(always)]
     pub fn lock (&self) -> MutexFuture<'_, T> {
         MutexFuture {
-            guard: Some(MutexGuard {
-                inner: self,
-            })
+            mutex: Some(self),
+            node: Node::new()
         }
     }
 
+    /// Attempts to lock the mutex from a hand-written ```poll``` method, queueing ```waiter``` (and the task's waker) if it's locked.
+    /// The task is woken once the waiter may get the mutex, and should then call ```poll_lock``` again with the same waiter.
+    ///
+    /// A waiter stays on the queue until the mutex is acquired, it's [```cancel```](PtrMutexWaiter::cancel)led or it's dropped.
+    #[inline]
+    pub fn poll_lock<'a> (&'a self, waiter: Pin<&mut MutexWaiter<'a, T>>, cx: &mut Context<'_>) -> Poll<MutexGuard<'a, T>> {
+        let waiter = unsafe { waiter.get_unchecked_mut() };
+        if self.inner.poll_lock(waiter.node_for(self), cx).is_ready() {
+            waiter.mutex = None;
+            return Poll::Ready(MutexGuard::new(self))
+        }
+
+        waiter.mutex = Some(self);
+        Poll::Pending
+    }
+
+    /// Returns a future that resolves to a guard once the mutex is acquired, or gives up once ```timeout``` has passed on ```timer```.
+    /// If it gives up, it's taken off the mutex's queue without swallowing anyone's wakeup.
     #[inline(always)]
-    pub fn try_lock_owned (self: Rc<Self>) -> Option<OwnedMutexGuard<T>> {
-        if self.inner.try_lock() {
-            return Some(OwnedMutexGuard {
-                inner: self,
-            })
+    pub fn lock_timeout<Tm: Timer> (&self, timer: &Tm, timeout: Duration) -> LockTimeoutFuture<MutexFuture<'_, T>, Tm::Delay> {
+        LockTimeoutFuture {
+            lock: Some(self.lock()),
+            delay: timer.delay(timeout)
         }
+    }
+}
 
-        None
+impl<T: ?Sized> Mutex<T> {
+    /// Attempts to lock the mutex through ```this```, which the returned guard holds on to
+    #[inline(always)]
+    pub fn try_lock_ptr<P: StableDeref<Target = Self>> (this: P) -> Option<PtrMutexGuard<P>> {
+        this.inner.try_lock_raw().then(|| PtrMutexGuard::new(this))
     }
 
+    /// Blocks the current thread until the mutex is locked through ```this```, which the returned guard holds on to
     #[cfg(feature = "sync")]
     #[inline(always)]
-    pub fn lock_blocking_owned (self: Rc<Self>) -> OwnedMutexGuard<T> {
-        self.inner.lock_blocking();
-        OwnedMutexGuard {
-            inner: self,
-        }
+    pub fn lock_blocking_ptr<P: StableDeref<Target = Self>> (this: P) -> PtrMutexGuard<P> {
+        this.inner.lock_blocking_raw();
+        PtrMutexGuard::new(this)
     }
 
+    /// Returns a future that locks the mutex through ```this```, which the resulting guard holds on to
     #[inline(always)]
-    pub fn lock_owned (self: Rc<Self>) -> OwnedMutexFuture<T> {
-        OwnedMutexFuture {
-            guard: Some(OwnedMutexGuard {
-                inner: self,
-            })
+    pub fn lock_ptr<P: StableDeref<Target = Self>> (this: P) -> PtrMutexFuture<P> {
+        PtrMutexFuture {
+            mutex: Some(this),
+            node: Node::new()
         }
     }
 
+    /// Like [```poll_lock```](Mutex::poll_lock), but locks the mutex through ```this```, which the resulting guard (and the waiter, while it's queued) keeps a copy of
+    #[inline]
+    pub fn poll_lock_ptr<P: CloneStableDeref<Target = Self>> (this: &P, waiter: Pin<&mut PtrMutexWaiter<P>>, cx: &mut Context<'_>) -> Poll<PtrMutexGuard<P>> {
+        let waiter = unsafe { waiter.get_unchecked_mut() };
+        if this.inner.poll_lock(waiter.node_for(this), cx).is_ready() {
+            waiter.mutex = None;
+            return Poll::Ready(PtrMutexGuard::new(this.clone()))
+        }
+
+        if waiter.mutex.is_none() {
+            waiter.mutex = Some(this.clone());
+        }
+
+        Poll::Pending
+    }
+
+    /// Like [```lock_ptr```](Mutex::lock_ptr), but gives up once ```timeout``` has passed on ```timer```
     #[inline(always)]
-    pub fn try_lock_atomic (self: Arc<Self>) -> Option<AtomicMutexGuard<T>> {
-        if self.inner.try_lock() {
-            return Some(AtomicMutexGuard {
-                inner: self,
-            })
+    pub fn lock_ptr_timeout<P: StableDeref<Target = Self>, Tm: Timer> (this: P, timer: &Tm, timeout: Duration) -> LockTimeoutFuture<PtrMutexFuture<P>, Tm::Delay> {
+        LockTimeoutFuture {
+            lock: Some(Self::lock_ptr(this)),
+            delay: timer.delay(timeout)
         }
+    }
+}
 
-        None
+#[cfg(feature = "alloc")]
+impl<T: ?Sized> Mutex<T> {
+    #[inline(always)]
+    pub fn try_lock_owned (self: Rc<Self>) -> Option<OwnedMutexGuard<T>> {
+        Self::try_lock_ptr(self)
+    }
+
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn lock_blocking_owned (self: Rc<Self>) -> OwnedMutexGuard<T> {
+        Self::lock_blocking_ptr(self)
+    }
+
+    #[inline(always)]
+    pub fn lock_owned (self: Rc<Self>) -> OwnedMutexFuture<T> {
+        Self::lock_ptr(self)
+    }
+
+    /// Like [```poll_lock```](Mutex::poll_lock), but the resulting guard keeps the mutex alive through an [```Rc```]
+    #[inline(always)]
+    pub fn poll_lock_owned (self: &Rc<Self>, waiter: Pin<&mut OwnedMutexWaiter<T>>, cx: &mut Context<'_>) -> Poll<OwnedMutexGuard<T>> {
+        Self::poll_lock_ptr(self, waiter, cx)
+    }
+
+    #[inline(always)]
+    pub fn try_lock_atomic (self: Arc<Self>) -> Option<AtomicMutexGuard<T>> {
+        Self::try_lock_ptr(self)
     }
 
     #[cfg(feature = "sync")]
     #[inline(always)]
     pub fn lock_blocking_atomic (self: Arc<Self>) -> AtomicMutexGuard<T> {
-        self.inner.lock_blocking();
-        AtomicMutexGuard {
-            inner: self,
-        }
+        Self::lock_blocking_ptr(self)
     }
 
     #[inline(always)]
     pub fn lock_atomic (self: Arc<Self>) -> AtomicMutexFuture<T> {
-        AtomicMutexFuture {
-            guard: Some(AtomicMutexGuard {
-                inner: self,
-            })
-        }
+        Self::lock_ptr(self)
+    }
+
+    /// Like [```poll_lock```](Mutex::poll_lock), but the resulting guard keeps the mutex alive through an [```Arc```]
+    #[inline(always)]
+    pub fn poll_lock_atomic (self: &Arc<Self>, waiter: Pin<&mut AtomicMutexWaiter<T>>, cx: &mut Context<'_>) -> Poll<AtomicMutexGuard<T>> {
+        Self::poll_lock_ptr(self, waiter, cx)
+    }
+}
+
+#[cfg(feature = "poison")]
+impl<T: ?Sized> Mutex<T> {
+    /// Like [```try_lock```](Mutex::try_lock), but returns an error if a thread panicked while holding the lock.
+    /// The guard can still be recovered from the error.
+    #[inline(always)]
+    pub fn try_lock_poisoned (&self) -> TryLockResult<MutexGuard<'_, T>> {
+        poison::try_locked(self.try_lock(), MutexGuard::is_poisoned)
+    }
+
+    /// Like [```lock_blocking```](Mutex::lock_blocking), but returns an error if a thread panicked while holding the lock
+    #[inline(always)]
+    pub fn lock_blocking_poisoned (&self) -> LockResult<MutexGuard<'_, T>> {
+        let guard = self.lock_blocking();
+        poison::locked(guard.is_poisoned(), guard)
+    }
+
+    /// Like [```lock```](Mutex::lock), but resolves to an error if a thread panicked while holding the lock
+    #[inline(always)]
+    pub fn lock_poisoned (&self) -> PoisonFuture<MutexFuture<'_, T>> {
+        PoisonFuture { lock: self.lock() }
+    }
+
+    /// Like [```try_lock_ptr```](Mutex::try_lock_ptr), but returns an error if a thread panicked while holding the lock
+    #[inline(always)]
+    pub fn try_lock_ptr_poisoned<P: StableDeref<Target = Self>> (this: P) -> TryLockResult<PtrMutexGuard<P>> {
+        poison::try_locked(Self::try_lock_ptr(this), PtrMutexGuard::is_poisoned)
+    }
+
+    /// Like [```lock_blocking_ptr```](Mutex::lock_blocking_ptr), but returns an error if a thread panicked while holding the lock
+    #[inline(always)]
+    pub fn lock_blocking_ptr_poisoned<P: StableDeref<Target = Self>> (this: P) -> LockResult<PtrMutexGuard<P>> {
+        let guard = Self::lock_blocking_ptr(this);
+        poison::locked(guard.is_poisoned(), guard)
+    }
+
+    /// Like [```lock_ptr```](Mutex::lock_ptr), but resolves to an error if a thread panicked while holding the lock
+    #[inline(always)]
+    pub fn lock_ptr_poisoned<P: StableDeref<Target = Self>> (this: P) -> PoisonFuture<PtrMutexFuture<P>> {
+        PoisonFuture { lock: Self::lock_ptr(this) }
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Returns the mutex's name, if it has one
+    #[inline(always)]
+    pub fn name (&self) -> Option<&'static str> {
+        self.inner.name
+    }
+
+    /// Returns ```true``` if a thread panicked while holding the lock
+    #[cfg(feature = "poison")]
+    #[inline(always)]
+    pub fn is_poisoned (&self) -> bool {
+        self.poison.get()
+    }
+
+    /// Clears the poisoned state of the mutex
+    #[cfg(feature = "poison")]
+    #[inline(always)]
+    pub fn clear_poison (&self) {
+        self.poison.clear()
+    }
+
+    /// Returns a snapshot of the mutex's contention statistics
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn stats (&self) -> crate::MutexStats {
+        self.inner.stats()
+    }
+
+    /// Resets the mutex's contention statistics
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn reset_stats (&self) {
+        self.inner.reset_stats()
+    }
+
+    /// Returns the mutex's identity in the lock-order graph, as reported by a [```LockOrderViolation```](crate::LockOrderViolation)
+    #[cfg(feature = "deadlock-detection")]
+    #[inline(always)]
+    pub fn lock_id (&self) -> crate::LockId {
+        self.inner.lock_id()
     }
 }
 
 impl<T> Debug for Mutex<T> {
     #[inline(always)]
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("Mutex").field("locked", &self.inner.locked).finish()
+        let mut f = f.debug_struct("Mutex");
+        if let Some(name) = self.inner.name {
+            f.field("name", &name);
+        }
+
+        f.field("locked", &self.inner.is_locked()).finish()
     }
 }
 
-unsafe impl<T: Send> Send for Mutex<T> {}
-unsafe impl<T: Sync> Sync for Mutex<T> {}
\ No newline at end of file
+// the data is only ever reached by whoever holds the lock, one thread at a time, so sharing the mutex only needs it to be ```Send```
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+// a panic while the mutex is locked poisons it, so whoever locks it next is told the data may be broken
+#[cfg(feature = "poison")]
+impl<T: ?Sized> UnwindSafe for Mutex<T> {}
+#[cfg(feature = "poison")]
+impl<T: ?Sized> RefUnwindSafe for Mutex<T> {}
\ No newline at end of file
@@ -0,0 +1,238 @@
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use core::fmt::Debug;
+#[cfg(feature = "alloc")]
+use alloc::{rc::Rc, sync::Arc};
+use crate::{StableDeref, guards::*, movable::MovableRwLock, queue::Node, shim::UnsafeCell};
+
+/// A readers-writer lock, attached to a value
+pub struct RwLock<T: ?Sized> {
+    pub(crate) inner: MovableRwLock,
+    pub(crate) data: UnsafeCell<T>,
+}
+
+impl<T> RwLock<T> {
+    loom_const_fn! {
+        /// Creates a new readers-writer lock
+        #[inline(always)]
+        pub fn new (data: T) -> Self {
+            Self {
+                inner: MovableRwLock::new(),
+                data: UnsafeCell::new(data),
+            }
+        }
+    }
+
+    loom_const_fn! {
+        /// Creates a new readers-writer lock from it's parts
+        #[inline(always)]
+        pub fn from_raw_parts (lock: MovableRwLock, data: T) -> Self {
+            Self { 
+                inner: lock,
+                data: UnsafeCell::new(data)
+            }
+        }
+    }
+
+    /// Consumes the lock and returns its underlying data
+    #[inline(always)]
+    pub fn into_inner (self) -> T {
+        self.data.into_inner()
+    }
+
+    /// Consumes the lock and returns its parts
+    #[inline(always)]
+    pub fn into_raw_parts (self) -> (MovableRwLock, T) {
+        (self.inner, self.data.into_inner())
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Attempts to acquire a read lock, returning a [```RwLockReadGuard```](crate::guards::RwLockReadGuard) if it's successful, and ```None``` otherwise
+    #[inline(always)]
+    pub fn try_read (&self) -> Option<RwLockReadGuard<'_, T>> {
+        if self.inner.try_read() {
+            return Some(RwLockReadGuard {
+                inner: self,
+            })
+        }
+
+        None
+    }
+
+    /// Blocks the current thread until a read lock is acquired, returning a [```RwLockReadGuard```](crate::guards::RwLockReadGuard)
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn read_blocking (&self) -> RwLockReadGuard<'_, T> {
+        self.inner.read_blocking();
+        RwLockReadGuard {
+            inner: self,
+        }
+    }
+
+    #[inline(always)]
+    pub fn read (&self) -> RwLockReadFuture<'_, T> {
+        RwLockReadFuture {
+            lock: Some(self),
+            node: Node::new()
+        }
+    }
+
+    /// Attempts to acquire a write lock, returning a [```RwLockWriteGuard```](crate::guards::RwLockWriteGuard) if it's successful, and ```None``` otherwise
+    #[inline(always)]
+    pub fn try_write (&self) -> Option<RwLockWriteGuard<'_, T>> {
+        if self.inner.try_write() {
+            return Some(RwLockWriteGuard {
+                inner: self,
+            })
+        }
+
+        None
+    }
+
+    /// Blocks the current thread until a write lock is acquired, returning a [```RwLockWriteGuard```](crate::guards::RwLockWriteGuard)
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn write_blocking (&self) -> RwLockWriteGuard<'_, T> {
+        self.inner.write_blocking();
+        RwLockWriteGuard {
+            inner: self,
+        }
+    }
+
+    #[inline(always)]
+    pub fn write (&self) -> RwLockWriteFuture<'_, T> {
+        RwLockWriteFuture {
+            lock: Some(self),
+            node: Node::new(),
+            locked: false
+        }
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Attempts to acquire a read lock through ```this```, which the returned guard holds on to
+    #[inline(always)]
+    pub fn try_read_ptr<P: StableDeref<Target = Self>> (this: P) -> Option<PtrRwLockReadGuard<P>> {
+        this.inner.try_read().then(|| PtrRwLockReadGuard { inner: this })
+    }
+
+    /// Blocks the current thread until a read lock is acquired through ```this```, which the returned guard holds on to
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn read_blocking_ptr<P: StableDeref<Target = Self>> (this: P) -> PtrRwLockReadGuard<P> {
+        this.inner.read_blocking();
+        PtrRwLockReadGuard { inner: this }
+    }
+
+    /// Returns a future that acquires a read lock through ```this```, which the resulting guard holds on to
+    #[inline(always)]
+    pub fn read_ptr<P: StableDeref<Target = Self>> (this: P) -> PtrRwLockReadFuture<P> {
+        PtrRwLockReadFuture {
+            lock: Some(this),
+            node: Node::new()
+        }
+    }
+
+    /// Attempts to acquire a write lock through ```this```, which the returned guard holds on to
+    #[inline(always)]
+    pub fn try_write_ptr<P: StableDeref<Target = Self>> (this: P) -> Option<PtrRwLockWriteGuard<P>> {
+        this.inner.try_write().then(|| PtrRwLockWriteGuard { inner: this })
+    }
+
+    /// Blocks the current thread until a write lock is acquired through ```this```, which the returned guard holds on to
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn write_blocking_ptr<P: StableDeref<Target = Self>> (this: P) -> PtrRwLockWriteGuard<P> {
+        this.inner.write_blocking();
+        PtrRwLockWriteGuard { inner: this }
+    }
+
+    /// Returns a future that acquires a write lock through ```this```, which the resulting guard holds on to
+    #[inline(always)]
+    pub fn write_ptr<P: StableDeref<Target = Self>> (this: P) -> PtrRwLockWriteFuture<P> {
+        PtrRwLockWriteFuture {
+            lock: Some(this),
+            node: Node::new(),
+            locked: false
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ?Sized> RwLock<T> {
+    #[inline(always)]
+    pub fn try_read_owned (self: Rc<Self>) -> Option<OwnedRwLockReadGuard<T>> {
+        Self::try_read_ptr(self)
+    }
+
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn read_blocking_owned (self: Rc<Self>) -> OwnedRwLockReadGuard<T> {
+        Self::read_blocking_ptr(self)
+    }
+
+    #[inline(always)]
+    pub fn read_owned (self: Rc<Self>) -> OwnedRwLockReadFuture<T> {
+        Self::read_ptr(self)
+    }
+
+    #[inline(always)]
+    pub fn try_write_owned (self: Rc<Self>) -> Option<OwnedRwLockWriteGuard<T>> {
+        Self::try_write_ptr(self)
+    }
+
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn write_blocking_owned (self: Rc<Self>) -> OwnedRwLockWriteGuard<T> {
+        Self::write_blocking_ptr(self)
+    }
+
+    #[inline(always)]
+    pub fn write_owned (self: Rc<Self>) -> OwnedRwLockWriteFuture<T> {
+        Self::write_ptr(self)
+    }
+
+    #[inline(always)]
+    pub fn try_read_atomic (self: Arc<Self>) -> Option<AtomicRwLockReadGuard<T>> {
+        Self::try_read_ptr(self)
+    }
+
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn read_blocking_atomic (self: Arc<Self>) -> AtomicRwLockReadGuard<T> {
+        Self::read_blocking_ptr(self)
+    }
+
+    #[inline(always)]
+    pub fn read_atomic (self: Arc<Self>) -> AtomicRwLockReadFuture<T> {
+        Self::read_ptr(self)
+    }
+
+    #[inline(always)]
+    pub fn try_write_atomic (self: Arc<Self>) -> Option<AtomicRwLockWriteGuard<T>> {
+        Self::try_write_ptr(self)
+    }
+
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn write_blocking_atomic (self: Arc<Self>) -> AtomicRwLockWriteGuard<T> {
+        Self::write_blocking_ptr(self)
+    }
+
+    #[inline(always)]
+    pub fn write_atomic (self: Arc<Self>) -> AtomicRwLockWriteFuture<T> {
+        Self::write_ptr(self)
+    }
+}
+
+impl<T> Debug for RwLock<T> {
+    #[inline(always)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RwLock").field("inner", &self.inner).finish()
+    }
+}
+
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
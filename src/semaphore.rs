@@ -0,0 +1,277 @@
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use core::{task::{Poll, Context}, fmt::Debug, pin::Pin};
+#[cfg(feature = "alloc")]
+use alloc::{rc::Rc, sync::Arc};
+use crate::{guards::*, shim::atomic::{AtomicUsize, Ordering}, queue::{Queue, Entries, Node, WaitState, WakeList}, waker::Waker};
+#[cfg(feature = "sync")]
+use crate::waker::Parker;
+
+/// Set while there are waiters on the queue, so that nobody can take permits from under them
+const QUEUED : usize = 1;
+const ONE_PERMIT : usize = 2;
+
+/// An async counting semaphore.
+///
+/// Waiters are served in FIFO order: while a large request is waiting for enough permits to be available,
+/// smaller requests queue up behind it instead of taking them first.
+pub struct Semaphore {
+    /// Number of available permits (in units of [```ONE_PERMIT```]), plus the [```QUEUED```] bit
+    pub(crate) state: AtomicUsize,
+    pub(crate) queue: Queue
+}
+
+impl Semaphore {
+    /// Maximum number of permits a semaphore can hold
+    pub const MAX_PERMITS : usize = usize::MAX / ONE_PERMIT;
+
+    loom_const_fn! {
+        /// Creates a new semaphore with the specified number of permits
+        #[inline(always)]
+        pub fn new (permits: usize) -> Self {
+            assert!(permits <= Self::MAX_PERMITS, "too many permits");
+            Self {
+                state: AtomicUsize::new(permits * ONE_PERMIT),
+                queue: Queue::new()
+            }
+        }
+    }
+
+    /// Returns the number of permits that are currently available
+    #[inline(always)]
+    pub fn available_permits (&self) -> usize {
+        self.state.load(Ordering::Relaxed) / ONE_PERMIT
+    }
+
+    /// Adds ```n``` permits to the semaphore, handing them to the waiters at the front of the queue if there are any
+    #[inline]
+    pub fn add_permits (&self, n: usize) {
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            let new = match n.checked_mul(ONE_PERMIT).and_then(|n| state.checked_add(n)) {
+                Some(x) => x,
+                None => panic!("too many permits")
+            };
+
+            match self.state.compare_exchange_weak(state, new, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(x) => state = x
+            }
+        }
+
+        if state & QUEUED != 0 {
+            self.wake_handed_off()
+        }
+    }
+
+    /// Attempts to acquire ```n``` permits, returning a [```SemaphorePermit```](crate::guards::SemaphorePermit) if it's successful, and ```None``` otherwise
+    #[inline(always)]
+    pub fn try_acquire (&self, n: usize) -> Option<SemaphorePermit<'_>> {
+        if self.try_take(n) {
+            return Some(SemaphorePermit { inner: self, permits: n })
+        }
+
+        None
+    }
+
+    /// Blocks the current thread until ```n``` permits are acquired, returning a [```SemaphorePermit```](crate::guards::SemaphorePermit)
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn acquire_blocking (&self, n: usize) -> SemaphorePermit<'_> {
+        self.take_blocking(n);
+        SemaphorePermit { inner: self, permits: n }
+    }
+
+    #[inline(always)]
+    pub fn acquire (&self, n: usize) -> SemaphoreFuture<'_> {
+        SemaphoreFuture {
+            semaphore: Some(self),
+            permits: n,
+            node: Node::new()
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Semaphore {
+    #[inline(always)]
+    pub fn try_acquire_owned (self: Rc<Self>, n: usize) -> Option<OwnedSemaphorePermit> {
+        if self.try_take(n) {
+            return Some(OwnedSemaphorePermit { inner: self, permits: n })
+        }
+
+        None
+    }
+
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn acquire_blocking_owned (self: Rc<Self>, n: usize) -> OwnedSemaphorePermit {
+        self.take_blocking(n);
+        OwnedSemaphorePermit { inner: self, permits: n }
+    }
+
+    #[inline(always)]
+    pub fn acquire_owned (self: Rc<Self>, n: usize) -> OwnedSemaphoreFuture {
+        OwnedSemaphoreFuture {
+            semaphore: Some(self),
+            permits: n,
+            node: Node::new()
+        }
+    }
+
+    #[inline(always)]
+    pub fn try_acquire_atomic (self: Arc<Self>, n: usize) -> Option<AtomicSemaphorePermit> {
+        if self.try_take(n) {
+            return Some(AtomicSemaphorePermit { inner: self, permits: n })
+        }
+
+        None
+    }
+
+    #[cfg(feature = "sync")]
+    #[inline(always)]
+    pub fn acquire_blocking_atomic (self: Arc<Self>, n: usize) -> AtomicSemaphorePermit {
+        self.take_blocking(n);
+        AtomicSemaphorePermit { inner: self, permits: n }
+    }
+
+    #[inline(always)]
+    pub fn acquire_atomic (self: Arc<Self>, n: usize) -> AtomicSemaphoreFuture {
+        AtomicSemaphoreFuture {
+            semaphore: Some(self),
+            permits: n,
+            node: Node::new()
+        }
+    }
+}
+
+impl Semaphore {
+    /// Takes ```n``` permits if they're available and nobody is queued before us
+    #[inline]
+    fn try_take (&self, n: usize) -> bool {
+        let mut state = self.state.load(Ordering::Acquire);
+        loop {
+            if state & QUEUED != 0 || state / ONE_PERMIT < n { return false }
+            match self.state.compare_exchange_weak(state, state - n * ONE_PERMIT, Ordering::Acquire, Ordering::Acquire) {
+                Ok(_) => return true,
+                Err(x) => state = x
+            }
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    #[inline]
+    fn take_blocking (&self, n: usize) {
+        if self.try_take(n) { return }
+
+        let parker = Parker::current();
+        let node = core::pin::pin!(Node::new());
+        if self.enqueue(n, node.as_ref(), Waker::Sync(parker.clone())) { return }
+
+        loop {
+            parker.park();
+            if self.queue.register_sync(node.as_ref(), &parker) { return }
+        }
+    }
+
+    /// Queues a waiter for ```n``` permits, unless nobody is waiting before it and they're already available,
+    /// in which case they're taken and ```true``` is returned.
+    #[inline]
+    fn enqueue (&self, n: usize, node: Pin<&Node>, waker: Waker) -> bool {
+        self.queue.with(|queue| {
+            // from here on, permits can only be taken with the queue locked
+            let state = self.state.fetch_or(QUEUED, Ordering::Acquire);
+            if !queue.has_waiters() && state / ONE_PERMIT >= n {
+                self.state.fetch_sub((n * ONE_PERMIT) | QUEUED, Ordering::Relaxed);
+                return true
+            }
+
+            queue.push(node, waker, n);
+            false
+        })
+    }
+
+    /// Hands the available permits to the waiters at the front of the queue and wakes them, a batch at a time
+    #[inline]
+    fn wake_handed_off (&self) {
+        loop {
+            let mut wakers = WakeList::new();
+            let more = self.queue.with(|queue| self.handoff(queue, &mut wakers));
+            wakers.wake();
+            if !more { break }
+        }
+    }
+
+    /// Hands the available permits to the waiters at the front of the queue, for as long as there are enough of them
+    /// and room left in ```wakers```. Returns ```true``` if it stopped because ```wakers``` is full.
+    /// Must be called with the queue locked.
+    #[inline]
+    fn handoff (&self, queue: &mut Entries<'_>, wakers: &mut WakeList) -> bool {
+        let mut taken = 0;
+        let mut permits = self.state.load(Ordering::Acquire) / ONE_PERMIT;
+        let more = queue.handoff_while(wakers, |n| {
+            if permits < n { return false }
+            permits -= n;
+            taken += n;
+            true
+        });
+
+        // permits are only handed out while the queued bit is set, so nobody took them from under us
+        if taken > 0 {
+            self.state.fetch_sub(taken * ONE_PERMIT, Ordering::AcqRel);
+        }
+
+        // once nobody is waiting, permits can be taken without locking the queue again
+        if !queue.has_waiters() {
+            self.state.fetch_and(!QUEUED, Ordering::Release);
+        }
+
+        more
+    }
+
+    /// Attempts to acquire ```n``` permits, queueing the task's waker on ```node``` if there aren't enough of them
+    #[allow(clippy::useless_conversion)]
+    #[inline]
+    pub(crate) fn poll_acquire (&self, n: usize, node: Pin<&Node>, cx: &mut Context<'_>) -> Poll<()> {
+        let acquired = match node.is_queued() {
+            false => self.try_take(n) || self.enqueue(n, node, cx.waker().clone().into()),
+            true => self.queue.register(node, cx.waker())
+        };
+
+        if acquired {
+            return Poll::Ready(());
+        }
+
+        Poll::Pending
+    }
+
+    /// Removes the waiter's node from the queue, giving back it's permits if they had already been handed to it
+    #[inline]
+    pub(crate) fn cancel (&self, n: usize, node: Pin<&Node>) {
+        if !node.is_queued() { return }
+
+        let mut wakers = WakeList::new();
+        let (state, more) = self.queue.with(|queue| match queue.remove(node) {
+            WaitState::HandedOff => (WaitState::HandedOff, false),
+            // the waiters that were queued behind it may be able to go through now
+            state => (state, self.handoff(queue, &mut wakers))
+        });
+
+        wakers.wake();
+        if more {
+            self.wake_handed_off()
+        }
+
+        if state == WaitState::HandedOff {
+            self.add_permits(n)
+        }
+    }
+}
+
+impl Debug for Semaphore {
+    #[inline(always)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Semaphore").field("permits", &self.available_permits()).finish()
+    }
+}
@@ -0,0 +1,48 @@
+//! Atomics, cells and threads used by the crate's synchronization protocols.
+//! Under ```cfg(loom)``` they're swapped for loom's, so that the protocols can be model-checked.
+//!
+//! Loom doesn't model everything the way the hardware does, so a couple of operations differ under it:
+//! - ```SeqCst``` fences are treated as ```AcqRel```, so [```Queue::is_empty```](crate::queue::Queue::is_empty) pairs with queueing through read-modify-writes instead.
+//! - A plain store racing with a read-modify-write can be ordered before it, so stores that release a lock or notify a waiter are swaps.
+
+cfg_if::cfg_if! {
+    if #[cfg(loom)] {
+        pub(crate) use loom::{sync::atomic, hint::spin_loop, cell::UnsafeCell};
+        #[cfg(feature = "std")]
+        pub(crate) use loom::{thread, thread_local};
+    } else {
+        pub(crate) use core::{sync::atomic, hint::spin_loop};
+        #[cfg(feature = "std")]
+        extern crate std;
+        #[cfg(feature = "std")]
+        pub(crate) use std::{thread, thread_local};
+
+        /// [```UnsafeCell```](core::cell::UnsafeCell) with loom's closure-based API
+        #[repr(transparent)]
+        pub(crate) struct UnsafeCell<T: ?Sized> (core::cell::UnsafeCell<T>);
+
+        impl<T> UnsafeCell<T> {
+            #[inline(always)]
+            pub const fn new (data: T) -> Self {
+                Self(core::cell::UnsafeCell::new(data))
+            }
+
+            #[inline(always)]
+            pub fn into_inner (self) -> T {
+                self.0.into_inner()
+            }
+        }
+
+        impl<T: ?Sized> UnsafeCell<T> {
+            #[inline(always)]
+            pub fn with<R, F: FnOnce(*const T) -> R> (&self, f: F) -> R {
+                f(self.0.get())
+            }
+
+            #[inline(always)]
+            pub fn with_mut<R, F: FnOnce(*mut T) -> R> (&self, f: F) -> R {
+                f(self.0.get())
+            }
+        }
+    }
+}
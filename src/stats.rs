@@ -0,0 +1,200 @@
+cfg_if::cfg_if! {
+    if #[cfg(feature = "stats")] {
+        use crate::{shim::atomic::{AtomicUsize, Ordering}, queue::Queue, clock::Nanos};
+
+        /// Contention counters of a [```MovableMutex```](crate::movable::MovableMutex)
+        pub(crate) struct Stats {
+            acquisitions: AtomicUsize,
+            contended: AtomicUsize,
+            blocking_waits: AtomicUsize,
+            async_waits: AtomicUsize,
+            max_queue_len: AtomicUsize,
+            #[cfg(feature = "std")]
+            times: Times
+        }
+
+        /// Wait and hold times, in nanoseconds
+        #[cfg(feature = "std")]
+        struct Times {
+            total_wait: crate::shim::atomic::AtomicU64,
+            max_wait: crate::shim::atomic::AtomicU64,
+            total_hold: crate::shim::atomic::AtomicU64,
+            max_hold: crate::shim::atomic::AtomicU64
+        }
+
+        /// Snapshot of a mutex's contention statistics, returned by [```Mutex::stats```](crate::Mutex::stats)
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+        pub struct MutexStats {
+            /// Number of times the mutex was acquired
+            pub acquisitions: usize,
+            /// Number of acquisitions that had to wait, because the mutex was locked on the first attempt
+            pub contended: usize,
+            /// Number of times a thread started blocking on the mutex, including waits that were later given up
+            pub blocking_waits: usize,
+            /// Number of times a future started waiting on the mutex, including waits that were later cancelled
+            pub async_waits: usize,
+            /// Longest the mutex's queue of waiters has been
+            pub max_queue_len: usize,
+            /// Total time spent waiting by contended acquisitions
+            #[cfg(feature = "std")]
+            pub total_wait: core::time::Duration,
+            /// Longest time a contended acquisition waited
+            #[cfg(feature = "std")]
+            pub max_wait: core::time::Duration,
+            /// Total time the mutex was held
+            #[cfg(feature = "std")]
+            pub total_hold: core::time::Duration,
+            /// Longest time the mutex was held
+            #[cfg(feature = "std")]
+            pub max_hold: core::time::Duration
+        }
+
+        impl Stats {
+            loom_const_fn! {
+                #[inline(always)]
+                pub fn new () -> Self {
+                    Self {
+                        acquisitions: AtomicUsize::new(0),
+                        contended: AtomicUsize::new(0),
+                        blocking_waits: AtomicUsize::new(0),
+                        async_waits: AtomicUsize::new(0),
+                        max_queue_len: AtomicUsize::new(0),
+                        #[cfg(feature = "std")]
+                        times: Times::new()
+                    }
+                }
+            }
+
+            /// The mutex was acquired, after waiting for ```wait``` if it was contended
+            #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+            #[inline(always)]
+            pub fn acquired (&self, wait: Option<Nanos>) {
+                self.acquisitions.fetch_add(1, Ordering::Relaxed);
+                if let Some(wait) = wait {
+                    self.contended.fetch_add(1, Ordering::Relaxed);
+                    #[cfg(feature = "std")]
+                    self.times.waited(wait);
+                }
+            }
+
+            /// A waiter found the mutex locked on it's first attempt
+            #[inline(always)]
+            pub fn waiting (&self, blocking: bool) {
+                match blocking {
+                    true => self.blocking_waits.fetch_add(1, Ordering::Relaxed),
+                    false => self.async_waits.fetch_add(1, Ordering::Relaxed)
+                };
+            }
+
+            /// A waiter was queued
+            #[inline(always)]
+            pub fn queued (&self, queue: &Queue) {
+                self.max_queue_len.fetch_max(queue.len(), Ordering::Relaxed);
+            }
+
+            /// The mutex is about to be unlocked, after being held for ```hold``` (if it was timed)
+            #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+            #[inline(always)]
+            pub fn released (&self, hold: Option<Nanos>) {
+                #[cfg(feature = "std")]
+                if let Some(hold) = hold {
+                    self.times.held(hold)
+                }
+            }
+
+            #[inline]
+            pub fn get (&self) -> MutexStats {
+                MutexStats {
+                    acquisitions: self.acquisitions.load(Ordering::Relaxed),
+                    contended: self.contended.load(Ordering::Relaxed),
+                    blocking_waits: self.blocking_waits.load(Ordering::Relaxed),
+                    async_waits: self.async_waits.load(Ordering::Relaxed),
+                    max_queue_len: self.max_queue_len.load(Ordering::Relaxed),
+                    #[cfg(feature = "std")]
+                    total_wait: Times::duration(&self.times.total_wait),
+                    #[cfg(feature = "std")]
+                    max_wait: Times::duration(&self.times.max_wait),
+                    #[cfg(feature = "std")]
+                    total_hold: Times::duration(&self.times.total_hold),
+                    #[cfg(feature = "std")]
+                    max_hold: Times::duration(&self.times.max_hold)
+                }
+            }
+
+            #[inline]
+            pub fn reset (&self) {
+                self.acquisitions.store(0, Ordering::Relaxed);
+                self.contended.store(0, Ordering::Relaxed);
+                self.blocking_waits.store(0, Ordering::Relaxed);
+                self.async_waits.store(0, Ordering::Relaxed);
+                self.max_queue_len.store(0, Ordering::Relaxed);
+                #[cfg(feature = "std")]
+                self.times.reset();
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl Times {
+            loom_const_fn! {
+                #[inline(always)]
+                fn new () -> Self {
+                    use crate::shim::atomic::AtomicU64;
+                    Self {
+                        total_wait: AtomicU64::new(0),
+                        max_wait: AtomicU64::new(0),
+                        total_hold: AtomicU64::new(0),
+                        max_hold: AtomicU64::new(0)
+                    }
+                }
+            }
+
+            #[inline(always)]
+            fn waited (&self, nanos: Nanos) {
+                self.total_wait.fetch_add(nanos, Ordering::Relaxed);
+                self.max_wait.fetch_max(nanos, Ordering::Relaxed);
+            }
+
+            #[inline(always)]
+            fn held (&self, nanos: Nanos) {
+                self.total_hold.fetch_add(nanos, Ordering::Relaxed);
+                self.max_hold.fetch_max(nanos, Ordering::Relaxed);
+            }
+
+            #[inline(always)]
+            fn reset (&self) {
+                self.total_wait.store(0, Ordering::Relaxed);
+                self.max_wait.store(0, Ordering::Relaxed);
+                self.total_hold.store(0, Ordering::Relaxed);
+                self.max_hold.store(0, Ordering::Relaxed);
+            }
+
+            #[inline(always)]
+            fn duration (nanos: &crate::shim::atomic::AtomicU64) -> core::time::Duration {
+                core::time::Duration::from_nanos(nanos.load(Ordering::Relaxed))
+            }
+        }
+    } else {
+        use crate::{queue::Queue, clock::Nanos};
+
+        pub(crate) struct Stats;
+
+        impl Stats {
+            #[inline(always)]
+            pub const fn new () -> Self {
+                Self
+            }
+
+            #[inline(always)]
+            pub fn acquired (&self, _: Option<Nanos>) {}
+
+            #[inline(always)]
+            pub fn waiting (&self, _: bool) {}
+
+            #[inline(always)]
+            pub fn queued (&self, _: &Queue) {}
+
+            #[inline(always)]
+            pub fn released (&self, _: Option<Nanos>) {}
+        }
+    }
+}
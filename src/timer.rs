@@ -0,0 +1,51 @@
+use core::{future::Future, time::Duration};
+
+/// An executor's timer, which the ```lock_timeout``` family of methods use to give up on a lock.
+///
+/// With the ```tokio``` and ```futures-timer``` features, it's implemented by ```TokioTimer``` and ```FuturesTimer``` respectively.
+pub trait Timer {
+    /// Future that resolves once the timeout has passed
+    type Delay: Future<Output = ()>;
+
+    /// Returns a future that resolves after ```timeout```
+    fn delay (&self, timeout: Duration) -> Self::Delay;
+}
+
+impl<T: ?Sized + Timer> Timer for &T {
+    type Delay = T::Delay;
+
+    #[inline(always)]
+    fn delay (&self, timeout: Duration) -> Self::Delay {
+        T::delay(self, timeout)
+    }
+}
+
+/// [```Timer```] backed by tokio's [```sleep```](tokio::time::sleep). It must be used from within a tokio runtime.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioTimer;
+
+#[cfg(feature = "tokio")]
+impl Timer for TokioTimer {
+    type Delay = tokio::time::Sleep;
+
+    #[inline(always)]
+    fn delay (&self, timeout: Duration) -> Self::Delay {
+        tokio::time::sleep(timeout)
+    }
+}
+
+/// [```Timer```] backed by [```futures_timer::Delay```], which works with any executor
+#[cfg(feature = "futures-timer")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FuturesTimer;
+
+#[cfg(feature = "futures-timer")]
+impl Timer for FuturesTimer {
+    type Delay = futures_timer::Delay;
+
+    #[inline(always)]
+    fn delay (&self, timeout: Duration) -> Self::Delay {
+        futures_timer::Delay::new(timeout)
+    }
+}
@@ -0,0 +1,57 @@
+cfg_if::cfg_if! {
+    if #[cfg(feature = "tracing")] {
+        use core::{fmt::Display, time::Duration};
+        use crate::{movable::MovableMutex, clock::Nanos};
+
+        /// How a mutex shows up in events: it's name if it has one, and it's address otherwise
+        struct Label<'a> (&'a MovableMutex);
+
+        impl Display for Label<'_> {
+            #[inline]
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self.0.name {
+                    Some(name) => f.write_str(name),
+                    None => write!(f, "{:p}", self.0)
+                }
+            }
+        }
+
+        #[inline(always)]
+        pub(crate) fn requested (mutex: &MovableMutex, blocking: bool) {
+            tracing::trace!(mutex = %Label(mutex), blocking, "lock requested")
+        }
+
+        #[inline(always)]
+        pub(crate) fn contended (mutex: &MovableMutex, blocking: bool) {
+            tracing::debug!(mutex = %Label(mutex), blocking, "lock contended")
+        }
+
+        #[inline(always)]
+        pub(crate) fn acquired (mutex: &MovableMutex, wait: Option<Nanos>) {
+            let wait = Duration::from_nanos(wait.unwrap_or_default());
+            tracing::trace!(mutex = %Label(mutex), ?wait, "lock acquired")
+        }
+
+        #[inline(always)]
+        pub(crate) fn released (mutex: &MovableMutex, hold: Option<Nanos>) {
+            match hold {
+                Some(hold) => tracing::trace!(mutex = %Label(mutex), hold = ?Duration::from_nanos(hold), "lock released"),
+                None => tracing::trace!(mutex = %Label(mutex), "lock released")
+            }
+        }
+    } else {
+        use crate::{movable::MovableMutex, clock::Nanos};
+
+        #[inline(always)]
+        pub(crate) fn requested (_: &MovableMutex, _: bool) {}
+
+        #[inline(always)]
+        pub(crate) fn contended (_: &MovableMutex, _: bool) {}
+
+        #[inline(always)]
+        pub(crate) fn acquired (_: &MovableMutex, _: Option<Nanos>) {}
+
+        #[inline(always)]
+        pub(crate) fn released (_: &MovableMutex, _: Option<Nanos>) {}
+    }
+}
@@ -1,13 +1,19 @@
-extern crate alloc;
-use alloc::sync::Arc;
-
 cfg_if::cfg_if! {
     if #[cfg(feature = "sync")] {
-        use crate::{Flag, TRUE};
+        extern crate alloc;
+        use crate::shim::atomic::Ordering;
+        use alloc::sync::Arc;
+        use crate::{Flag, TRUE, FALSE};
+
+        /// When a blocking wait gives up. Without ```std```'s clock (and under loom) there's none, so a wait never times out.
+        #[cfg(all(feature = "std", not(loom)))]
+        pub type Deadline = std::time::Instant;
+        #[cfg(not(all(feature = "std", not(loom))))]
+        pub type Deadline = core::convert::Infallible;
 
         pub enum Waker {
             Async (core::task::Waker),
-            Sync (Arc<Flag>)
+            Sync (Arc<Parker>)
         }
 
         impl Waker {
@@ -15,7 +21,15 @@ cfg_if::cfg_if! {
             pub fn wake (self) {
                 match self {
                     Self::Async (w) => w.wake(),
-                    Self::Sync (f) => f.store(TRUE, core::sync::atomic::Ordering::Release)
+                    Self::Sync (p) => p.unpark()
+                }
+            }
+
+            #[inline(always)]
+            pub fn will_wake (&self, other: &core::task::Waker) -> bool {
+                match self {
+                    Self::Async (w) => w.will_wake(other),
+                    Self::Sync (_) => false
                 }
             }
         }
@@ -26,7 +40,106 @@ cfg_if::cfg_if! {
                 Self::Async(x)
             }
         }
+
+        /// Blocks a thread until it's woken by a [```Waker::Sync```]
+        pub struct Parker {
+            notified: Flag,
+            #[cfg(feature = "std")]
+            thread: crate::shim::thread::Thread
+        }
+
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "std")] {
+                extern crate std;
+                use crate::shim::{thread, thread_local};
+
+                thread_local! {
+                    static PARKER : Arc<Parker> = Arc::new(Parker {
+                        notified: Flag::new(FALSE),
+                        thread: thread::current()
+                    });
+                }
+
+                impl Parker {
+                    /// Returns the current thread's parker, which is reused by every blocking wait on it
+                    #[inline(always)]
+                    pub fn current () -> Arc<Self> {
+                        PARKER.with(Arc::clone)
+                    }
+
+                    /// Parks the thread until the parker is notified
+                    #[inline]
+                    pub fn park (&self) {
+                        while self.notified.swap(FALSE, Ordering::Acquire) == FALSE {
+                            thread::park()
+                        }
+                    }
+
+                    /// Parks the thread until the parker is notified or ```deadline``` (if any) is reached, returning ```true``` if it was notified
+                    #[cfg(not(loom))]
+                    #[inline]
+                    pub fn park_until (&self, deadline: Option<Deadline>) -> bool {
+                        let Some(deadline) = deadline else {
+                            self.park();
+                            return true
+                        };
+
+                        while self.notified.swap(FALSE, Ordering::Acquire) == FALSE {
+                            let now = std::time::Instant::now();
+                            if now >= deadline { return false }
+                            thread::park_timeout(deadline - now)
+                        }
+                        true
+                    }
+
+                    /// Parks the thread until the parker is notified
+                    #[cfg(loom)]
+                    #[inline(always)]
+                    pub fn park_until (&self, _: Option<Deadline>) -> bool {
+                        self.park();
+                        true
+                    }
+
+                    #[inline(always)]
+                    pub fn unpark (&self) {
+                        #[cfg(not(loom))]
+                        self.notified.store(TRUE, Ordering::Release);
+                        #[cfg(loom)]
+                        self.notified.swap(TRUE, Ordering::Release);
+                        self.thread.unpark()
+                    }
+                }
+            } else {
+                impl Parker {
+                    /// Returns a new parker. Without ```std``` there is no way to park a thread, so it spins instead
+                    #[inline(always)]
+                    pub fn current () -> Arc<Self> {
+                        Arc::new(Self { notified: Flag::new(FALSE) })
+                    }
+
+                    /// Spins until the parker is notified
+                    #[inline]
+                    pub fn park (&self) {
+                        while self.notified.swap(FALSE, Ordering::Acquire) == FALSE {
+                            crate::shim::spin_loop()
+                        }
+                    }
+
+                    /// Spins until the parker is notified
+                    #[inline(always)]
+                    pub fn park_until (&self, _: Option<Deadline>) -> bool {
+                        self.park();
+                        true
+                    }
+
+                    #[inline(always)]
+                    pub fn unpark (&self) {
+                        self.notified.store(TRUE, Ordering::Release)
+                    }
+                }
+            }
+        }
     } else {
         pub type Waker = core::task::Waker;
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,116 @@
+use std::{sync::Arc, thread};
+use async_mutex::{movable::MovableMutex, guards::MovableMutexGuard};
+
+/// Runs ```f``` on a thread with the given name, returning it's panic message
+fn panic_message<F: FnOnce() + Send + 'static> (name: &str, f: F) -> String {
+    let err = thread::Builder::new().name(name.into()).spawn(f).unwrap().join().unwrap_err();
+    match err.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(err) => err.downcast_ref::<&str>().unwrap().to_string()
+    }
+}
+
+#[test]
+fn unlocked_by_non_owner () {
+    let mutex = Arc::new(MovableMutex::new().named("checked"));
+    let guard = thread::Builder::new().name("owner".into()).spawn({
+        let mutex = mutex.clone();
+        move || mutex.clone().lock_blocking_atomic()
+    }).unwrap().join().unwrap();
+
+    let msg = panic_message("intruder", {
+        let mutex = mutex.clone();
+        move || unsafe { mutex.unlock() }
+    });
+
+    assert!(msg.contains("'intruder'"), "{msg}");
+    assert!(msg.contains("'owner'"), "{msg}");
+    assert!(msg.contains("mutex 'checked'"), "{msg}");
+    drop(guard);
+}
+
+#[test]
+fn double_unlock () {
+    let mutex = Arc::new(MovableMutex::new());
+    thread::Builder::new().name("first".into()).spawn({
+        let mutex = mutex.clone();
+        move || {
+            mutex.lock_blocking().forget();
+            unsafe { mutex.unlock() }
+        }
+    }).unwrap().join().unwrap();
+
+    let msg = panic_message("second", move || unsafe { mutex.unlock() });
+    assert!(msg.contains("'second'"), "{msg}");
+    assert!(msg.contains("already been unlocked by thread 'first'"), "{msg}");
+}
+
+#[test]
+fn created_locked () {
+    let mutex = Arc::new(MovableMutex::locked());
+    let msg = panic_message("unlocker", {
+        let mutex = mutex.clone();
+        move || unsafe { mutex.unlock() }
+    });
+    assert!(msg.contains("created locked and nobody has acquired it"), "{msg}");
+
+    // claiming it with a token makes it it's owner's to unlock
+    drop(unsafe { MovableMutexGuard::from_raw(&mutex) });
+    assert!(mutex.try_lock().is_some());
+}
+
+#[tokio::test]
+async fn async_owner () {
+    let mutex = Arc::new(MovableMutex::new());
+    let guard = mutex.lock().await;
+
+    let msg = panic_message("intruder", {
+        let mutex = mutex.clone();
+        move || unsafe { mutex.unlock() }
+    });
+    assert!(msg.contains("held by lock token #"), "{msg}");
+    drop(guard);
+
+    // a forgotten token can only be unlocked by hand on the thread that forgot it
+    mutex.lock().await.forget();
+    let msg = panic_message("intruder", {
+        let mutex = mutex.clone();
+        move || unsafe { mutex.unlock() }
+    });
+    assert!(msg.contains("forgotten by lock token #"), "{msg}");
+    unsafe { mutex.unlock() }
+
+    // remaking the token keeps it's number
+    let raw = mutex.lock().await.into_raw();
+    let token = unsafe { MovableMutexGuard::from_raw(raw) };
+    let msg = panic_message("intruder", {
+        let mutex = mutex.clone();
+        move || unsafe { mutex.unlock() }
+    });
+    assert!(msg.contains("held by lock token #"), "{msg}");
+
+    token.forget();
+    unsafe { mutex.unlock() }
+}
+
+#[test]
+fn forgotten_by_another_thread () {
+    let mutex = Arc::new(MovableMutex::new().named("checked"));
+    thread::Builder::new().name("owner".into()).spawn({
+        let mutex = mutex.clone();
+        move || mutex.lock_blocking().forget()
+    }).unwrap().join().unwrap();
+
+    let msg = panic_message("intruder", {
+        let mutex = mutex.clone();
+        move || unsafe { mutex.unlock() }
+    });
+    assert!(msg.contains("'intruder'"), "{msg}");
+    assert!(msg.contains("forgotten by thread 'owner'"), "{msg}");
+
+    let msg = panic_message("intruder", {
+        let mutex = mutex.clone();
+        move || drop(unsafe { MovableMutexGuard::from_raw(&mutex) })
+    });
+    assert!(msg.contains("forgotten by thread 'owner'"), "{msg}");
+}
@@ -0,0 +1,138 @@
+use std::{sync::Arc, thread, task::Context, pin::pin};
+use async_mutex::{Mutex, Condvar};
+use futures::{Future, future::join_all, task::noop_waker_ref};
+
+const SIZE : usize = 100;
+
+#[test]
+fn notify_one () {
+    let mutex = Mutex::new(0);
+    let condvar = Condvar::new();
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let mut first = pin!(condvar.wait(mutex.try_lock().unwrap()));
+    assert!(first.as_mut().poll(&mut cx).is_pending());
+    let mut second = pin!(condvar.wait(mutex.try_lock().unwrap()));
+    assert!(second.as_mut().poll(&mut cx).is_pending());
+
+    condvar.notify_one();
+    assert!(second.as_mut().poll(&mut cx).is_pending());
+    let guard = match first.poll(&mut cx) {
+        std::task::Poll::Ready(guard) => guard,
+        std::task::Poll::Pending => panic!("the first waiter wasn't notified")
+    };
+
+    // the mutex is locked again, so the notified waiter has to wait for it
+    condvar.notify_all();
+    assert!(second.as_mut().poll(&mut cx).is_pending());
+    drop(guard);
+    assert!(second.poll(&mut cx).is_ready());
+}
+
+#[test]
+fn notify_all () {
+    let mutex = Mutex::new(0);
+    let condvar = Condvar::new();
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let mut waiters = Vec::with_capacity(SIZE);
+    for _ in 0..SIZE {
+        let mut fut = Box::pin(condvar.wait(mutex.try_lock().unwrap()));
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+        waiters.push(fut);
+    }
+
+    // there are more waiters than can be woken at once, so they're notified in batches
+    condvar.notify_all();
+    for mut fut in waiters {
+        match fut.as_mut().poll(&mut cx) {
+            std::task::Poll::Ready(mut guard) => *guard += 1,
+            std::task::Poll::Pending => panic!("a waiter wasn't notified")
+        }
+    }
+
+    assert_eq!(mutex.into_inner(), SIZE);
+}
+
+#[test]
+fn cancelled () {
+    let mutex = Mutex::new(0);
+    let condvar = Condvar::new();
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let mut first = Box::pin(condvar.wait(mutex.try_lock().unwrap()));
+    assert!(first.as_mut().poll(&mut cx).is_pending());
+    let mut second = pin!(condvar.wait(mutex.try_lock().unwrap()));
+    assert!(second.as_mut().poll(&mut cx).is_pending());
+
+    // the notification goes to the first waiter, which gives it up
+    condvar.notify_one();
+    drop(first);
+    assert!(second.poll(&mut cx).is_ready());
+}
+
+#[test]
+fn unpolled () {
+    let mutex = Mutex::new(0);
+    let condvar = Condvar::new();
+
+    // the mutex isn't released until the future is polled
+    let fut = condvar.wait(mutex.try_lock().unwrap());
+    assert!(mutex.try_lock().is_none());
+    drop(fut);
+    assert!(mutex.try_lock().is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mixed () {
+    let pair = Arc::new((Mutex::new(0), Condvar::new()));
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for i in 0..SIZE {
+        let pair = pair.clone();
+        // blocking waiters must stay off the runtime's workers, or they could starve the notifier
+        handles.push(if rand::random::<bool>() {
+            tokio::task::spawn_blocking(move || {
+                let (mutex, condvar) = &*pair;
+                let mut guard = mutex.lock_blocking();
+                while *guard != i {
+                    guard = condvar.wait_blocking(guard);
+                }
+                *guard += 1;
+                condvar.notify_all();
+            })
+        } else {
+            tokio::spawn(async move {
+                let (mutex, condvar) = &*pair;
+                let mut guard = condvar.wait_while(mutex.lock().await, |x| *x != i).await;
+                *guard += 1;
+                condvar.notify_all();
+            })
+        });
+    }
+
+    join_all(handles).await;
+    assert_eq!(*pair.0.lock().await, SIZE);
+}
+
+#[test]
+fn atomic () {
+    let pair = Arc::new((Arc::new(Mutex::new(false)), Condvar::new()));
+    let handle = {
+        let pair = pair.clone();
+        thread::spawn(move || {
+            let (mutex, condvar) = &*pair;
+            *mutex.lock_blocking() = true;
+            condvar.notify_one();
+        })
+    };
+
+    let (mutex, condvar) = &*pair;
+    let mut guard = mutex.clone().lock_blocking_atomic();
+    while !*guard {
+        guard = condvar.wait_blocking(guard);
+    }
+
+    drop(guard);
+    handle.join().unwrap();
+}
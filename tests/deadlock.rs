@@ -0,0 +1,82 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use async_mutex::Mutex;
+use futures::executor::block_on;
+
+fn violation<F: FnOnce()> (f: F) -> Option<String> {
+    let err = catch_unwind(AssertUnwindSafe(f)).err()?;
+    Some(*err.downcast::<String>().unwrap())
+}
+
+#[test]
+fn blocking () {
+    let a = Mutex::new(0);
+    let b = Mutex::new(0);
+
+    {
+        let _a = a.lock_blocking();
+        let _b = b.lock_blocking();
+    }
+
+    let msg = violation(|| {
+        let _b = b.lock_blocking();
+        let _a = a.lock_blocking();
+    }).expect("the inversion wasn't reported");
+
+    assert!(msg.contains(&format!("mutex {} -> mutex {} -> mutex {}", b.lock_id(), a.lock_id(), b.lock_id())), "{msg}");
+}
+
+#[test]
+fn consistent () {
+    let a = Mutex::new(0);
+    let b = Mutex::new(0);
+
+    for _ in 0..2 {
+        assert!(violation(|| {
+            let _a = a.lock_blocking();
+            let _b = b.lock_blocking();
+        }).is_none());
+    }
+}
+
+#[test]
+fn future () {
+    let a = Mutex::new(0);
+    let b = Mutex::new(0);
+    let c = Mutex::new(0);
+
+    block_on(async {
+        let _a = a.lock().await;
+        let _b = b.lock().await;
+    });
+
+    block_on(async {
+        let _b = b.lock().await;
+        let _c = c.lock().await;
+    });
+
+    // a -> b -> c -> a
+    let msg = violation(|| block_on(async {
+        let _c = c.lock().await;
+        let _a = a.lock().await;
+    })).expect("the inversion wasn't reported");
+
+    assert!(msg.contains(&format!("mutex {} -> mutex {} -> mutex {} -> mutex {}", c.lock_id(), a.lock_id(), b.lock_id(), c.lock_id())), "{msg}");
+}
+
+#[test]
+fn named () {
+    let a = Mutex::new(0).named("first");
+    let b = Mutex::new(0).named("second");
+
+    {
+        let _a = a.lock_blocking();
+        let _b = b.lock_blocking();
+    }
+
+    let msg = violation(|| {
+        let _b = b.lock_blocking();
+        let _a = a.lock_blocking();
+    }).expect("the inversion wasn't reported");
+
+    assert!(msg.contains(&format!("mutex 'second' ({}) -> mutex 'first' ({})", b.lock_id(), a.lock_id())), "{msg}");
+}
@@ -0,0 +1,121 @@
+use std::{sync::Arc, task::Context, pin::pin};
+use async_mutex::{Mutex, movable::Fairness};
+use futures::{Future, future::join_all, task::noop_waker_ref};
+
+const SIZE : usize = 1000;
+
+#[test]
+fn barging () {
+    let mutex = Mutex::new(0);
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let guard = mutex.try_lock().unwrap();
+    let mut fut = pin!(mutex.lock());
+    assert!(fut.as_mut().poll(&mut cx).is_pending());
+    drop(guard);
+
+    // the mutex was released, so anyone can take it before the waiter runs
+    let guard = mutex.try_lock().unwrap();
+    assert!(fut.as_mut().poll(&mut cx).is_pending());
+    drop(guard);
+    assert!(fut.poll(&mut cx).is_ready());
+}
+
+#[test]
+fn fifo () {
+    let mutex = Mutex::with_fairness(0, Fairness::Fifo);
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let guard = mutex.try_lock().unwrap();
+    let mut first = pin!(mutex.lock());
+    let mut second = pin!(mutex.lock());
+    assert!(first.as_mut().poll(&mut cx).is_pending());
+    assert!(second.as_mut().poll(&mut cx).is_pending());
+    drop(guard);
+
+    // ownership went straight to the first waiter, so nobody can barge in
+    assert!(mutex.try_lock().is_none());
+    assert!(second.as_mut().poll(&mut cx).is_pending());
+    let guard = match first.poll(&mut cx) {
+        std::task::Poll::Ready(guard) => guard,
+        std::task::Poll::Pending => panic!("ownership wasn't handed to the first waiter")
+    };
+
+    drop(guard);
+    assert!(mutex.try_lock().is_none());
+    assert!(second.poll(&mut cx).is_ready());
+}
+
+#[test]
+fn eventual () {
+    let mutex = Mutex::with_fairness(0, Fairness::Eventual);
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let guard = mutex.try_lock().unwrap();
+    let mut fut = pin!(mutex.lock());
+    assert!(fut.as_mut().poll(&mut cx).is_pending());
+    drop(guard);
+
+    // the first unlock is unfair, so the waiter gets barged past once...
+    let guard = mutex.try_lock().unwrap();
+    assert!(fut.as_mut().poll(&mut cx).is_pending());
+    drop(guard);
+
+    // ...but not twice
+    assert!(mutex.try_lock().is_none());
+    assert!(fut.poll(&mut cx).is_ready());
+}
+
+#[test]
+fn unlock_fair () {
+    let mutex = Mutex::new(0);
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let guard = mutex.try_lock().unwrap();
+    let mut fut = pin!(mutex.lock());
+    assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+    guard.unlock_fair();
+    assert!(mutex.try_lock().is_none());
+    assert!(fut.poll(&mut cx).is_ready());
+}
+
+#[test]
+fn handed_off_then_dropped () {
+    let mutex = Mutex::with_fairness(0, Fairness::Fifo);
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let guard = mutex.try_lock().unwrap();
+    let mut first = Box::pin(mutex.lock());
+    let mut second = pin!(mutex.lock());
+    assert!(first.as_mut().poll(&mut cx).is_pending());
+    assert!(second.as_mut().poll(&mut cx).is_pending());
+    drop(guard);
+
+    // the first waiter owns the mutex without ever being polled again, so dropping it must pass ownership on
+    drop(first);
+    assert!(mutex.try_lock().is_none());
+    assert!(second.poll(&mut cx).is_ready());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mixed () {
+    for fairness in [Fairness::Fifo, Fairness::Eventual] {
+        let mutex = Arc::new(Mutex::with_fairness(0, fairness));
+        let mut handles = Vec::with_capacity(SIZE);
+
+        for _ in 0..SIZE {
+            let mutex = mutex.clone();
+            // ownership may be handed to a task that isn't running yet, so blocking waiters must stay off the runtime's workers
+            handles.push(if rand::random::<bool>() {
+                tokio::task::spawn_blocking(move || *mutex.lock_blocking() += 1)
+            } else {
+                tokio::spawn(async move { *mutex.lock().await += 1 })
+            });
+        }
+
+        join_all(handles).await;
+        let inner = Arc::try_unwrap(mutex).unwrap();
+        assert_eq!(inner.into_inner(), SIZE);
+    }
+}
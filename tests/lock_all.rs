@@ -0,0 +1,106 @@
+use std::{sync::Arc, thread, task::Context, pin::pin};
+use async_mutex::{Mutex, lock_all, lock_all_blocking};
+use futures::{Future, future::try_join_all, task::noop_waker_ref};
+
+const SIZE : usize = 1000;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transfer () {
+    let accounts = Arc::new([Mutex::new(SIZE), Mutex::new(SIZE)]);
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for _ in 0..SIZE {
+        let accounts = accounts.clone();
+        handles.push(tokio::spawn(async move {
+            // both directions at once, which would deadlock if the order they're passed in was followed
+            let (from, to) = match rand::random::<bool>() {
+                true => (&accounts[0], &accounts[1]),
+                false => (&accounts[1], &accounts[0])
+            };
+
+            let (mut from, mut to) = lock_all((from, to)).await;
+            tokio::task::yield_now().await;
+            *from -= 1;
+            *to += 1;
+        }));
+    }
+
+    try_join_all(handles).await.unwrap();
+    let [a, b] = Arc::try_unwrap(accounts).unwrap();
+    assert_eq!(a.into_inner() + b.into_inner(), 2 * SIZE);
+}
+
+#[test]
+fn blocking () {
+    let accounts = Arc::new([Mutex::new(SIZE), Mutex::new(SIZE)]);
+    let mut handles = Vec::with_capacity(8);
+
+    for i in 0..8 {
+        let accounts = accounts.clone();
+        handles.push(thread::spawn(move || {
+            for _ in 0..(SIZE / 8) {
+                let [mut from, mut to] = match i % 2 {
+                    0 => lock_all_blocking([&accounts[0], &accounts[1]]),
+                    _ => lock_all_blocking([&accounts[1], &accounts[0]])
+                };
+
+                *from -= 1;
+                *to += 1;
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let [a, b] = Arc::try_unwrap(accounts).unwrap();
+    assert_eq!(a.into_inner() + b.into_inner(), 2 * SIZE);
+}
+
+#[test]
+fn cancelled () {
+    let a = Mutex::new(0);
+    let b = Mutex::new(String::new());
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    // whichever mutex comes first is locked, and the future waits for the other one
+    let guard = match (&a as *const _ as usize) < (&b as *const _ as usize) {
+        true => (None, Some(b.try_lock().unwrap())),
+        false => (Some(a.try_lock().unwrap()), None)
+    };
+
+    let mut fut = Box::pin(lock_all((&a, &b)));
+    assert!(fut.as_mut().poll(&mut cx).is_pending());
+    drop(fut);
+    drop(guard);
+
+    let mut fut = pin!(lock_all((&a, &b)));
+    assert!(fut.as_mut().poll(&mut cx).is_ready());
+}
+
+#[test]
+fn owned () {
+    let first = Arc::new(Mutex::new(1));
+    let second = Arc::new(Mutex::new(2));
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let mut fut = pin!(lock_all(vec![second.clone(), first.clone()]));
+    let guards = match fut.as_mut().poll(&mut cx) {
+        std::task::Poll::Ready(guards) => guards,
+        std::task::Poll::Pending => panic!("the mutexes weren't locked")
+    };
+
+    // guards come back in the order the mutexes were passed in
+    assert_eq!(guards.iter().map(|x| **x).collect::<Vec<_>>(), [2, 1]);
+    assert!(first.try_lock().is_none());
+    drop(guards);
+    assert!(first.try_lock().is_some());
+}
+
+#[test]
+#[should_panic]
+fn duplicate () {
+    let mutex = Mutex::new(0);
+    drop(lock_all([&mutex, &mutex]));
+}
@@ -0,0 +1,216 @@
+//! Model checks of the crate's atomic protocols. Run them with
+//! ```RUSTFLAGS="--cfg loom" cargo test --release --test loom```
+#![cfg(loom)]
+
+use std::{pin::pin, task::Context, future::Future};
+use loom::{sync::Arc, thread, future::block_on};
+use futures::task::noop_waker_ref;
+use async_mutex::{Mutex, RwLock, Condvar, Semaphore, movable::Fairness};
+
+/// Runs a model with three or more threads. The queue's spin lock makes an unbounded search blow up,
+/// so preemptions are bounded (unless ```LOOM_MAX_PREEMPTIONS``` says otherwise).
+fn model<F: Fn() + Sync + Send + 'static> (f: F) {
+    let mut builder = loom::model::Builder::new();
+    if builder.preemption_bound.is_none() {
+        builder.preemption_bound = Some(2);
+    }
+    builder.check(f)
+}
+
+#[test]
+fn handoff () {
+    loom::model(|| {
+        let mutex = Arc::new(Mutex::with_fairness(0, Fairness::Fifo));
+        let guard = mutex.try_lock().unwrap();
+
+        let handle = {
+            let mutex = mutex.clone();
+            thread::spawn(move || *mutex.lock_blocking() += 1)
+        };
+
+        // ownership is handed straight to the waiter if it's queued by now, and released otherwise
+        drop(guard);
+        *mutex.lock_blocking() += 1;
+        handle.join().unwrap();
+        assert_eq!(*mutex.try_lock().unwrap(), 2);
+    });
+}
+
+#[test]
+fn guarded_data () {
+    loom::model(|| {
+        // the data lives in a loom cell, so a lock or unlock that doesn't synchronize shows up as a race on it
+        let mutex = Arc::new(Mutex::new(Vec::new()));
+
+        let handle = {
+            let mutex = mutex.clone();
+            thread::spawn(move || mutex.lock_blocking().push(1))
+        };
+
+        block_on(mutex.lock()).push(2);
+        handle.join().unwrap();
+
+        let mut data = mutex.try_lock().unwrap().clone();
+        data.sort();
+        assert_eq!(data, [1, 2]);
+    });
+}
+
+#[test]
+fn register_racing_unlock () {
+    loom::model(|| {
+        let mutex = Arc::new(Mutex::new(0));
+        let guard = mutex.try_lock().unwrap();
+
+        let handle = {
+            let mutex = mutex.clone();
+            // a wakeup lost between queueing the waker and the unlock would leave this blocked forever
+            thread::spawn(move || *block_on(mutex.lock()) += 1)
+        };
+
+        drop(guard);
+        handle.join().unwrap();
+        assert_eq!(*mutex.try_lock().unwrap(), 1);
+    });
+}
+
+#[test]
+fn concurrent_queueing () {
+    model(|| {
+        let mutex = Arc::new(Mutex::new(0));
+        let guard = mutex.try_lock().unwrap();
+
+        let handles = (0..2).map(|_| {
+            let mutex = mutex.clone();
+            thread::spawn(move || *block_on(mutex.lock()) += 1)
+        }).collect::<Vec<_>>();
+
+        drop(guard);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(*mutex.try_lock().unwrap(), 2);
+    });
+}
+
+#[test]
+fn mixed () {
+    model(|| {
+        let mutex = Arc::new(Mutex::new(0));
+        let guard = mutex.try_lock().unwrap();
+
+        let blocking = {
+            let mutex = mutex.clone();
+            thread::spawn(move || *mutex.lock_blocking() += 1)
+        };
+
+        let future = {
+            let mutex = mutex.clone();
+            thread::spawn(move || *block_on(mutex.lock()) += 1)
+        };
+
+        drop(guard);
+        blocking.join().unwrap();
+        future.join().unwrap();
+        assert_eq!(*mutex.try_lock().unwrap(), 2);
+    });
+}
+
+#[test]
+fn semaphore () {
+    loom::model(|| {
+        let semaphore = Arc::new(Semaphore::new(0));
+
+        let handle = {
+            let semaphore = semaphore.clone();
+            thread::spawn(move || block_on(semaphore.acquire(2)).forget())
+        };
+
+        semaphore.add_permits(1);
+        semaphore.add_permits(1);
+        handle.join().unwrap();
+        assert_eq!(semaphore.available_permits(), 0);
+    });
+}
+
+#[test]
+fn write_racing_unlock_read () {
+    loom::model(|| {
+        let lock = Arc::new(RwLock::new(0));
+        let guard = lock.try_read().unwrap();
+
+        let handle = {
+            let lock = lock.clone();
+            // the writer waits on ```no_readers``` once it's announced itself, so the last reader has to wake it
+            thread::spawn(move || *block_on(lock.write()) += 1)
+        };
+
+        drop(guard);
+        handle.join().unwrap();
+        assert_eq!(*lock.try_read().unwrap(), 1);
+    });
+}
+
+#[test]
+fn condvar_wait_racing_notify () {
+    loom::model(|| {
+        let mutex = Arc::new(Mutex::new(false));
+        let condvar = Arc::new(Condvar::new());
+
+        let handle = {
+            let mutex = mutex.clone();
+            let condvar = condvar.clone();
+            thread::spawn(move || block_on(async {
+                // the waiter is queued on the condvar before the mutex is released, so the notification can't slip in between
+                let mut guard = mutex.lock().await;
+                while !*guard {
+                    guard = condvar.wait(guard).await;
+                }
+            }))
+        };
+
+        *mutex.lock_blocking() = true;
+        condvar.notify_one();
+        handle.join().unwrap();
+    });
+}
+
+/// A waiter that gives up while the mutex is being unlocked has to pass on whatever it was given
+fn cancel_racing_unlock (fairness: Fairness) {
+    model(move || {
+        let mutex = Arc::new(Mutex::with_fairness(0, fairness));
+        let guard = mutex.try_lock().unwrap();
+
+        let cancelled = {
+            let mutex = mutex.clone();
+            thread::spawn(move || {
+                let mut cx = Context::from_waker(noop_waker_ref());
+                let mut fut = pin!(mutex.lock());
+                if let std::task::Poll::Ready(mut guard) = fut.as_mut().poll(&mut cx) {
+                    *guard += 1;
+                }
+            })
+        };
+
+        let waiter = {
+            let mutex = mutex.clone();
+            thread::spawn(move || *block_on(mutex.lock()) += 1)
+        };
+
+        drop(guard);
+        cancelled.join().unwrap();
+        waiter.join().unwrap();
+        assert!(*mutex.try_lock().unwrap() >= 1);
+    });
+}
+
+#[test]
+fn cancel_handed_off () {
+    cancel_racing_unlock(Fairness::Fifo)
+}
+
+#[test]
+fn cancel_woken () {
+    cancel_racing_unlock(Fairness::Barging)
+}
@@ -0,0 +1,63 @@
+use std::{sync::Arc, rc::Rc, fmt::Debug};
+use async_mutex::{Mutex, guards::{MutexGuard, MappedMutexGuard, OwnedMutexGuard, AtomicMutexGuard}};
+
+struct Data {
+    name: &'static str,
+    values: [u32; 4]
+}
+
+#[test]
+fn borrowed () {
+    let mutex = Mutex::new(Data { name: "data", values: [0; 4] });
+
+    let mut values = MutexGuard::map(mutex.try_lock().unwrap(), |x| &mut x.values as &mut [u32]);
+    values[1] = 1;
+    assert!(mutex.try_lock().is_none());
+
+    // mapping again keeps the mutex locked
+    let mut value = MappedMutexGuard::map(values, |x| &mut x[2]);
+    *value = 2;
+    assert!(mutex.try_lock().is_none());
+    drop(value);
+
+    let guard = mutex.try_lock().unwrap();
+    assert_eq!(guard.values, [0, 1, 2, 0]);
+
+    // a failed map gives the guard back
+    let guard = match MutexGuard::try_map(guard, |x| x.values.get_mut(10)) {
+        Ok(_) => panic!("mapped to an out of bounds value"),
+        Err(guard) => guard
+    };
+    let name = MutexGuard::map(guard, |x| &mut x.name as &mut dyn Debug);
+    assert_eq!(format!("{:?}", &*name), "\"data\"");
+}
+
+#[tokio::test]
+async fn owned () {
+    let mutex = Rc::new(Mutex::new(Data { name: "data", values: [0; 4] }));
+
+    let mut value = OwnedMutexGuard::map(mutex.clone().lock_owned().await, |x| &mut x.values[3]);
+    *value = 3;
+    assert!(mutex.try_lock().is_none());
+    drop(value);
+
+    let guard = mutex.clone().try_lock_owned().unwrap();
+    assert_eq!(guard.values, [0, 0, 0, 3]);
+    assert!(OwnedMutexGuard::try_map(guard, |x| x.values.get_mut(4)).is_err());
+    assert!(mutex.try_lock().is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn atomic () {
+    let mutex = Arc::new(Mutex::new(Data { name: "data", values: [0; 4] }));
+
+    // the mapped guard keeps the mutex alive, and can be sent to another task
+    let value = AtomicMutexGuard::map(mutex.clone().lock_atomic().await, |x| &mut x.values[0]);
+    let handle = tokio::spawn(async move {
+        let mut value = value;
+        *value = 4;
+    });
+
+    handle.await.unwrap();
+    assert_eq!(mutex.lock().await.values, [4, 0, 0, 0]);
+}
@@ -1,6 +1,6 @@
-use std::{sync::Arc, thread};
-use async_mutex::movable::MovableMutex;
-use futures::future::join_all;
+use std::{sync::Arc, rc::Rc, thread, time::Duration, task::{Context, Waker, RawWaker, RawWakerVTable}, pin::pin};
+use async_mutex::{movable::MovableMutex, guards::{MovableMutexGuard, AtomicMovableMutexGuard}};
+use futures::{Future, FutureExt, future::{FusedFuture, join_all, try_join_all}};
 
 #[test]
 fn only_sync () {
@@ -12,12 +12,11 @@ fn only_sync () {
         let mutex = mutex.clone();
         handles.push(thread::spawn(move || {
             for j in 0..10 {
-                mutex.0.lock_blocking();
+                let _guard = mutex.0.lock_blocking();
                 unsafe {
                     let data = &mut *(mutex.1 as *const u32 as *mut u32);
                     *data += 1;
                     println!("({i}, {j}) = {data}");
-                    mutex.0.unlock();
                 };
             }
         }));
@@ -39,11 +38,10 @@ async fn only_async () {
     for _ in 0..1000 {
         let mutex = mutex.clone();
         handles.push(tokio::spawn(async move {
-            mutex.0.lock().await;
+            let _guard = mutex.0.lock().await;
             unsafe {
                 let data = &mut *(mutex.1 as *const u32 as *mut u32);
                 *data += 1;
-                mutex.0.unlock();
             };
         }));
     }
@@ -62,20 +60,113 @@ async fn mixed () {
         let mutex = mutex.clone();
         
         handles.push(tokio::spawn(async move {
-            if rand::random::<bool>() {
-                mutex.0.lock_blocking();
+            let _guard = if rand::random::<bool>() {
+                mutex.0.lock_blocking()
             } else {
-                mutex.0.lock().await;
-            }
+                mutex.0.lock().await
+            };
 
             unsafe {
                 let data = &mut *(mutex.1 as *const u32 as *mut u32);
                 *data += 1;
-                mutex.0.unlock();
             };
         }));
     }
 
     join_all(handles).await;
     assert_eq!(*mutex.1, 1000);
-}
\ No newline at end of file
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancelled () {
+    let data = Box::leak(Box::new(0u32));
+    let mutex = Arc::new((MovableMutex::new(), data));
+    let mut handles = Vec::with_capacity(1000);
+
+    for _ in 0..1000 {
+        let mutex = mutex.clone();
+        handles.push(tokio::spawn(async move {
+            let _guard = if rand::random::<bool>() {
+                let timeout = Duration::from_micros(rand::random::<u64>() % 100);
+                match tokio::time::timeout(timeout, mutex.0.lock()).await {
+                    Ok(guard) => guard,
+                    Err(_) => return 0
+                }
+            } else {
+                mutex.0.lock().await
+            };
+
+            tokio::task::yield_now().await;
+            unsafe {
+                let data = &mut *(mutex.1 as *const u32 as *mut u32);
+                *data += 1;
+            };
+            1
+        }));
+    }
+
+    let locked : u32 = try_join_all(handles).await.unwrap().into_iter().sum();
+    assert_eq!(*mutex.1, locked);
+}
+
+
+static UNLOCK_ON_CLONE : RawWakerVTable = RawWakerVTable::new(
+    |mutex| unsafe {
+        drop(MovableMutexGuard::from_raw(&*(mutex as *const MovableMutex)));
+        RawWaker::new(mutex, &NOOP)
+    },
+    |_| {}, |_| {}, |_| {}
+);
+
+static NOOP : RawWakerVTable = RawWakerVTable::new(|x| RawWaker::new(x, &NOOP), |_| {}, |_| {}, |_| {});
+
+/// The waker is cloned right before it's queued, so unlocking the mutex from ```clone```
+/// reproduces an unlock that lands between a waiter's failed ```try_lock``` and it's registration
+#[test]
+fn unlock_while_registering () {
+    static MUTEX : MovableMutex = MovableMutex::locked();
+    let waker = unsafe { Waker::from_raw(RawWaker::new(&MUTEX as *const MovableMutex as *const (), &UNLOCK_ON_CLONE)) };
+    
+    let mut fut = pin!(MUTEX.lock());
+    assert!(fut.as_mut().poll(&mut Context::from_waker(&waker)).is_ready());
+}
+
+#[test]
+fn tokens () {
+    let mutex = MovableMutex::new();
+    let guard = mutex.try_lock().unwrap();
+    assert!(mutex.try_lock().is_none());
+    drop(guard);
+
+    // a forgotten token leaves the mutex locked until it's unlocked by hand
+    mutex.try_lock().unwrap().forget();
+    assert!(mutex.try_lock().is_none());
+    unsafe { mutex.unlock() };
+
+    let raw = mutex.lock_blocking().into_raw();
+    assert!(mutex.try_lock().is_none());
+    drop(unsafe { MovableMutexGuard::from_raw(raw) });
+    assert!(mutex.try_lock().is_some());
+
+    let owned = Rc::new(MovableMutex::new());
+    let guard = owned.clone().try_lock_owned().unwrap();
+    assert!(owned.clone().try_lock_owned().is_none());
+    guard.unlock_fair();
+    drop(owned.lock_blocking_owned());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sent_token () {
+    let mutex = Arc::new(MovableMutex::new());
+    let mut fut = pin!(mutex.clone().lock_atomic());
+    let guard : AtomicMovableMutexGuard = fut.as_mut().await;
+    assert!(fut.is_terminated());
+
+    let mut contended = pin!(mutex.lock());
+    assert!(contended.as_mut().now_or_never().is_none());
+
+    // the mutex is unlocked from whichever thread the token ends up on
+    thread::spawn(move || drop(guard)).join().unwrap();
+    contended.await.unlock();
+    assert!(mutex.clone().try_lock_atomic().is_some());
+}
@@ -0,0 +1,92 @@
+use std::{sync::Arc, thread};
+use async_mutex::{Mutex, TryLockError, guards::MutexGuard};
+
+#[test]
+fn blocking () {
+    let mutex = Arc::new(Mutex::new(0));
+
+    let handle = {
+        let mutex = mutex.clone();
+        thread::spawn(move || {
+            let mut guard = mutex.lock_blocking_poisoned().unwrap();
+            *guard += 1;
+            panic!("failed while holding the lock");
+        })
+    };
+
+    assert!(handle.join().is_err());
+    assert!(mutex.is_poisoned());
+
+    // the data is still reachable through the error
+    let Err(err) = mutex.lock_blocking_poisoned() else { panic!("the mutex wasn't poisoned") };
+    let guard = err.into_inner();
+    assert_eq!(*guard, 1);
+    drop(guard);
+
+    mutex.clear_poison();
+    assert!(!mutex.is_poisoned());
+    assert!(mutex.try_lock_poisoned().is_ok());
+}
+
+#[test]
+fn try_lock () {
+    let mutex = Arc::new(Mutex::new(0));
+    let guard = mutex.try_lock_poisoned().unwrap();
+    assert!(matches!(mutex.try_lock_poisoned(), Err(TryLockError::WouldBlock)));
+    drop(guard);
+
+    let handle = {
+        let mutex = mutex.clone();
+        thread::spawn(move || {
+            let _guard = MutexGuard::map(mutex.try_lock().unwrap(), |x| x);
+            panic!("failed while holding a mapped guard");
+        })
+    };
+
+    assert!(handle.join().is_err());
+    assert!(matches!(mutex.try_lock_poisoned(), Err(TryLockError::Poisoned(_))));
+    assert!(matches!(Mutex::try_lock_ptr_poisoned(mutex.clone()), Err(TryLockError::Poisoned(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn future () {
+    let mutex = Arc::new(Mutex::new(0));
+
+    let handle = {
+        let mutex = mutex.clone();
+        tokio::spawn(async move {
+            let _guard = Mutex::lock_ptr_poisoned(mutex).await.unwrap();
+            panic!("failed while holding the lock");
+        })
+    };
+
+    assert!(handle.await.is_err());
+    assert!(mutex.lock_poisoned().await.is_err());
+
+    // the guard was released without panicking, so the lock isn't poisoned any further
+    mutex.clear_poison();
+    drop(mutex.lock_poisoned().await.unwrap());
+    assert!(!mutex.is_poisoned());
+}
+
+#[test]
+fn plain_methods () {
+    let mutex = Arc::new(Mutex::new(0));
+
+    let handle = {
+        let mutex = mutex.clone();
+        thread::spawn(move || {
+            let _guard = mutex.lock_blocking();
+            panic!("failed while holding the lock");
+        })
+    };
+
+    // the methods without ```_poisoned``` hand out the guard regardless, which can still be asked
+    assert!(handle.join().is_err());
+    let guard = mutex.lock_blocking();
+    assert!(guard.is_poisoned());
+    drop(guard);
+
+    assert!(mutex.try_lock().unwrap().is_poisoned());
+    assert!(Mutex::lock_blocking_ptr(mutex.clone()).is_poisoned());
+}
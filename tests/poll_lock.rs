@@ -0,0 +1,63 @@
+use std::{sync::Arc, collections::VecDeque, pin::{Pin, pin}, task::{Context, Poll}};
+use async_mutex::{Mutex, guards::{AtomicMutexWaiter, MutexWaiter}};
+use futures::{Stream, StreamExt, FutureExt, future::{join_all, poll_fn}, task::noop_waker_ref};
+
+const SIZE : usize = 1000;
+
+/// Hand-written stream that pops from a shared queue, locking it from ```poll_next```
+struct Drain {
+    queue: Arc<Mutex<VecDeque<usize>>>,
+    waiter: Pin<Box<AtomicMutexWaiter<VecDeque<usize>>>>
+}
+
+impl Stream for Drain {
+    type Item = usize;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let mut queue = futures::ready!(Mutex::poll_lock_atomic(&this.queue, this.waiter.as_mut(), cx));
+        Poll::Ready(queue.pop_front())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stream () {
+    let queue = Arc::new(Mutex::new((0..SIZE).collect::<VecDeque<_>>()));
+    let mut handles = Vec::with_capacity(8);
+
+    for _ in 0..8 {
+        let queue = queue.clone();
+        handles.push(tokio::spawn(async move {
+            let drain = Drain { queue, waiter: Box::pin(AtomicMutexWaiter::new()) };
+            drain.inspect(|_| std::thread::yield_now()).collect::<Vec<_>>().await
+        }));
+    }
+
+    let mut items = join_all(handles).await.into_iter().flat_map(Result::unwrap).collect::<Vec<_>>();
+    items.sort_unstable();
+    assert_eq!(items, (0..SIZE).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn dropped_waiter () {
+    let mutex = Mutex::new(0);
+    let guard = mutex.lock().await;
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    {
+        let mut waiter = pin!(MutexWaiter::new());
+        assert!(mutex.poll_lock(waiter.as_mut(), &mut cx).is_pending());
+        assert!(waiter.is_waiting());
+    }
+
+    // the dropped waiter left the queue, so the next one is woken
+    let mut waiter = pin!(MutexWaiter::new());
+    let next = poll_fn(|cx| mutex.poll_lock(waiter.as_mut(), cx).map(|mut guard| *guard += 1));
+    drop(guard);
+    next.await;
+    assert!(!waiter.is_waiting());
+
+    // and it can be reused
+    *poll_fn(|cx| mutex.poll_lock(waiter.as_mut(), cx)).now_or_never().unwrap() += 1;
+    assert_eq!(*mutex.try_lock().unwrap(), 2);
+}
@@ -0,0 +1,66 @@
+use std::{sync::Arc, cell::RefCell, thread, time::Duration};
+use async_mutex::{ReentrantMutex, OwnerId};
+use futures::future::try_join_all;
+
+const SIZE : usize = 1000;
+
+/// Plugin-style callback that re-enters the state it was called with
+fn notify (state: &ReentrantMutex<RefCell<Vec<usize>>>, depth: usize) {
+    let guard = state.lock_blocking();
+    assert_eq!(guard.depth(), depth);
+    guard.borrow_mut().push(depth);
+    if depth < 3 {
+        notify(state, depth + 1)
+    }
+}
+
+#[test]
+fn recursive () {
+    let state = Arc::new(ReentrantMutex::new(RefCell::new(Vec::new())));
+    let guard = state.lock_blocking();
+    notify(&state, 2);
+    assert_eq!(*guard.borrow(), [2, 3]);
+
+    // other threads have to wait until every guard is dropped
+    let handle = {
+        let state = state.clone();
+        thread::spawn(move || {
+            assert!(state.try_lock().is_none());
+            state.lock_blocking().borrow_mut().push(0);
+        })
+    };
+
+    thread::sleep(Duration::from_millis(50));
+    let again = state.try_lock().unwrap();
+    assert_eq!(again.depth(), 2);
+    drop(guard);
+    drop(again);
+
+    handle.join().unwrap();
+    assert_eq!(*state.try_lock().unwrap().borrow(), [2, 3, 0]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tasks () {
+    let mutex = Arc::new(ReentrantMutex::new(std::sync::atomic::AtomicUsize::new(0)));
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for _ in 0..SIZE {
+        let mutex = mutex.clone();
+        handles.push(tokio::spawn(async move {
+            let owner = OwnerId::new();
+            let outer = mutex.lock(owner).await;
+            let before = outer.load(std::sync::atomic::Ordering::Relaxed);
+            tokio::task::yield_now().await;
+
+            let inner = mutex.lock(owner).await;
+            assert_eq!(inner.depth(), 2);
+            assert!(mutex.try_lock_with(OwnerId::new()).is_none());
+            // nobody else got in while the task held the mutex
+            assert_eq!(inner.fetch_add(1, std::sync::atomic::Ordering::Relaxed), before);
+        }));
+    }
+
+    try_join_all(handles).await.unwrap();
+    assert_eq!(Arc::try_unwrap(mutex).unwrap().into_inner().into_inner(), SIZE);
+}
@@ -1,6 +1,6 @@
-use std::{sync::Arc, thread};
-use async_mutex::Mutex;
-use futures::future::{join_all, try_join_all};
+use std::{sync::{Arc, atomic::{AtomicUsize, Ordering}}, thread, time::{Duration, Instant}, task::{Context, Poll}, pin::{pin, Pin}, ops::Deref};
+use async_mutex::{Mutex, StableDeref, Timer, guards::MutexGuard, movable::Fairness};
+use futures::{Future, future::{join_all, try_join_all}, task::{ArcWake, waker}};
 
 const SIZE : usize = 10_000;
 //const SIZE : usize = 1000;
@@ -70,4 +70,309 @@ async fn mixed () {
     join_all(handles).await;
     let inner = Arc::try_unwrap(mutex).unwrap();
     assert_eq!(inner.into_inner(), SIZE);
-}
\ No newline at end of file
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancelled () {
+    let mutex = Arc::new(Mutex::new(0));
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for _ in 0..SIZE {
+        let mutex = mutex.clone();
+        handles.push(tokio::spawn(async move {
+            let mut data = if rand::random::<bool>() {
+                let timeout = Duration::from_micros(rand::random::<u64>() % 100);
+                match tokio::time::timeout(timeout, mutex.lock()).await {
+                    Ok(data) => data,
+                    Err(_) => return false
+                }
+            } else {
+                mutex.lock().await
+            };
+
+            *data += 1;
+            tokio::task::yield_now().await;
+            true
+        }));
+    }
+
+    let locked = try_join_all(handles).await.unwrap().into_iter().filter(|x| *x).count();
+    let inner = Arc::try_unwrap(mutex).unwrap();
+    assert_eq!(inner.into_inner(), locked);
+}
+
+#[derive(Default)]
+struct CountWaker (AtomicUsize);
+
+impl ArcWake for CountWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn repolled () {
+    let mutex = Mutex::new(0);
+    let guard = mutex.try_lock().unwrap();
+
+    let first = Arc::new(CountWaker::default());
+    let second = Arc::new(CountWaker::default());
+    let first_waker = waker(first.clone());
+    let second_waker = waker(second.clone());
+
+    let mut first_fut = Box::pin(mutex.lock());
+    let mut second_fut = pin!(mutex.lock());
+
+    assert!(first_fut.as_mut().poll(&mut Context::from_waker(&first_waker)).is_pending());
+    assert!(second_fut.as_mut().poll(&mut Context::from_waker(&second_waker)).is_pending());
+
+    // spurious polls must neither add slots nor move the first future behind the second one
+    for _ in 0..10 {
+        assert!(first_fut.as_mut().poll(&mut Context::from_waker(&first_waker)).is_pending());
+    }
+
+    drop(guard);
+    assert_eq!(first.0.load(Ordering::SeqCst), 1);
+    assert_eq!(second.0.load(Ordering::SeqCst), 0);
+
+    // the first future passes it's wakeup on when dropped
+    drop(first_fut);
+    assert_eq!(second.0.load(Ordering::SeqCst), 1);
+    assert!(matches!(second_fut.poll(&mut Context::from_waker(&second_waker)), Poll::Ready(_)));
+}
+
+/// A custom reference counted handle to a mutex
+#[derive(Clone)]
+struct Handle (Arc<Mutex<usize>>);
+
+impl Deref for Handle {
+    type Target = Mutex<usize>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+unsafe impl StableDeref for Handle {}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn custom_pointer () {
+    let handle = Handle(Arc::new(Mutex::new(0)));
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for _ in 0..SIZE {
+        let handle = handle.clone();
+        handles.push(tokio::spawn(async move {
+            let mut data = Mutex::lock_ptr(handle).await;
+            *data += 1;
+        }));
+    }
+
+    try_join_all(handles).await.unwrap();
+    assert_eq!(*Mutex::try_lock_ptr(handle).unwrap(), SIZE);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn third_party_pointer () {
+    let mutex = triomphe::Arc::new(Mutex::new(0));
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for _ in 0..SIZE {
+        let mutex = mutex.clone();
+        handles.push(tokio::spawn(async move {
+            let mut data = Mutex::lock_ptr(mutex).await;
+            *data += 1;
+        }));
+    }
+
+    try_join_all(handles).await.unwrap();
+
+    let guard = Mutex::lock_blocking_ptr(mutex.clone());
+    assert_eq!(*guard, SIZE);
+    assert!(Mutex::try_lock_ptr(mutex.clone()).is_none());
+    drop(guard);
+    assert!(Mutex::try_lock_ptr(mutex).is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pinned_pointer () {
+    let mutex = Arc::pin(Mutex::new(0));
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for _ in 0..SIZE {
+        let mutex = mutex.clone();
+        handles.push(tokio::spawn(async move {
+            let mut data = Mutex::lock_ptr(Pin::into_inner(mutex)).await;
+            *data += 1;
+        }));
+    }
+
+    try_join_all(handles).await.unwrap();
+    assert_eq!(*Mutex::try_lock_ptr(Pin::into_inner(mutex)).unwrap(), SIZE);
+}
+
+#[test]
+fn blocking_timeout () {
+    let mutex = Arc::new(Mutex::with_fairness(0, Fairness::Fifo));
+    let guard = mutex.try_lock().unwrap();
+
+    let start = Instant::now();
+    assert!(mutex.lock_blocking_timeout(Duration::from_millis(50)).is_none());
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert!(mutex.lock_blocking_until(Instant::now()).is_none());
+
+    // the timed out waiters must have left the queue, or they'd be handed the lock instead
+    let handle = {
+        let mutex = mutex.clone();
+        thread::spawn(move || *mutex.lock_blocking_timeout(Duration::from_secs(10)).unwrap() += 1)
+    };
+
+    thread::sleep(Duration::from_millis(50));
+    drop(guard);
+    handle.join().unwrap();
+    assert_eq!(*mutex.try_lock().unwrap(), 1);
+}
+
+#[derive(Clone, Copy)]
+struct Sleep;
+
+impl Timer for Sleep {
+    type Delay = Pin<Box<tokio::time::Sleep>>;
+
+    fn delay (&self, timeout: Duration) -> Self::Delay {
+        Box::pin(tokio::time::sleep(timeout))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn async_timeout () {
+    let mutex = Arc::new(Mutex::new(0));
+    let guard = mutex.lock().await;
+    assert!(mutex.lock_timeout(&Sleep, Duration::from_millis(50)).await.is_none());
+
+    let waiter = {
+        let mutex = mutex.clone();
+        tokio::spawn(async move { *mutex.lock_timeout(&Sleep, Duration::from_secs(10)).await.unwrap() += 1 })
+    };
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    drop(guard);
+    waiter.await.unwrap();
+    assert_eq!(*Mutex::lock_ptr_timeout(mutex, &Sleep, Duration::ZERO).await.unwrap(), 1);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(flavor = "multi_thread")]
+async fn tokio_timer () {
+    use async_mutex::TokioTimer;
+
+    let mutex = Arc::new(Mutex::new(0));
+    let guard = mutex.lock().await;
+    assert!(mutex.lock_timeout(&TokioTimer, Duration::from_millis(50)).await.is_none());
+    assert!(Mutex::lock_ptr_timeout(mutex.clone(), &TokioTimer, Duration::from_millis(50)).await.is_none());
+
+    let waiter = {
+        let mutex = mutex.clone();
+        tokio::spawn(async move { *Mutex::lock_ptr_timeout(mutex, &TokioTimer, Duration::from_secs(10)).await.unwrap() += 1 })
+    };
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    drop(guard);
+    waiter.await.unwrap();
+    assert_eq!(*mutex.lock_timeout(&TokioTimer, Duration::from_secs(10)).await.unwrap(), 1);
+}
+
+#[cfg(feature = "futures-timer")]
+#[test]
+fn futures_timer () {
+    use async_mutex::FuturesTimer;
+
+    let mutex = Mutex::new(0);
+    futures::executor::block_on(async {
+        let guard = mutex.lock().await;
+        assert!(mutex.lock_timeout(&FuturesTimer, Duration::from_millis(50)).await.is_none());
+
+        let release = async move {
+            FuturesTimer.delay(Duration::from_millis(50)).await;
+            drop(guard);
+        };
+
+        let (locked, ()) = futures::join!(mutex.lock_timeout(&FuturesTimer, Duration::from_secs(10)), release);
+        *locked.unwrap() += 1;
+    });
+
+    assert_eq!(mutex.into_inner(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn timed_out () {
+    let mutex = Arc::new(Mutex::with_fairness(0, Fairness::Fifo));
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for _ in 0..SIZE {
+        let mutex = mutex.clone();
+        handles.push(tokio::spawn(async move {
+            let mut data = if rand::random::<bool>() {
+                let timeout = Duration::from_micros(rand::random::<u64>() % 100);
+                match mutex.lock_timeout(&Sleep, timeout).await {
+                    Some(data) => data,
+                    None => return false
+                }
+            } else {
+                mutex.lock().await
+            };
+
+            *data += 1;
+            tokio::task::yield_now().await;
+            true
+        }));
+    }
+
+    let locked = try_join_all(handles).await.unwrap().into_iter().filter(|x| *x).count();
+    let inner = Arc::try_unwrap(mutex).unwrap();
+    assert_eq!(inner.into_inner(), locked);
+}
+
+#[test]
+fn unlocked_blocking () {
+    let mutex = Mutex::new(0);
+    let mut guard = mutex.lock_blocking();
+
+    // locking it again inside would deadlock if it weren't released
+    MutexGuard::unlocked_blocking(&mut guard, || *mutex.lock_blocking() += 1);
+    *guard += 1;
+
+    // it's locked again even if the closure panics
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| MutexGuard::unlocked_blocking(&mut guard, || panic!())));
+    assert!(res.is_err());
+    assert!(mutex.try_lock().is_none());
+    assert_eq!(*guard, 2);
+}
+
+#[tokio::test]
+async fn bump () {
+    let mutex = Arc::new(Mutex::new(Vec::new()));
+    let guard = mutex.clone().lock_atomic().await;
+    assert!(!guard.has_waiters());
+    let mut guard = guard.bump().await;
+
+    let waiter = tokio::spawn({
+        let mutex = mutex.clone();
+        async move { mutex.lock().await.push(1) }
+    });
+
+    tokio::task::yield_now().await;
+    assert!(guard.has_waiters());
+    guard.push(0);
+
+    // the waiter goes first, and the guard is back once it's done
+    let guard = guard.bump().await;
+    assert_eq!(*guard, [0, 1]);
+    drop(guard);
+    waiter.await.unwrap();
+
+    let guard = mutex.lock().await;
+    let (guard, len) = guard.unlocked_async(async { mutex.lock().await.len() }).await;
+    assert_eq!(len, 2);
+    assert_eq!(*guard, [0, 1]);
+}
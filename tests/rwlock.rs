@@ -0,0 +1,198 @@
+use std::{sync::Arc, thread, time::Duration, task::Context, pin::pin};
+use async_mutex::{RwLock, movable::MovableRwLock};
+use futures::{Future, future::{FusedFuture, try_join_all}, task::noop_waker_ref};
+
+const SIZE : usize = 10_000;
+
+#[test]
+fn only_sync () {
+    let lock = Arc::new(RwLock::new(0));
+    let mut handles = Vec::with_capacity(8);
+
+    for i in 0..8 {
+        let lock = lock.clone();
+        handles.push(thread::spawn(move || {
+            for _ in 0..(SIZE/8) {
+                if i % 2 == 0 {
+                    *lock.write_blocking() += 1;
+                } else {
+                    let data = lock.read_blocking();
+                    assert!(*data <= SIZE / 2);
+                }
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let inner = Arc::try_unwrap(lock).unwrap();
+    assert_eq!(inner.into_inner(), SIZE / 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn only_async () {
+    let lock = Arc::new(RwLock::new(0));
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for _ in 0..SIZE {
+        let lock = lock.clone();
+        handles.push(tokio::spawn(async move {
+            if rand::random::<bool>() {
+                *lock.write().await += 1;
+                return 1
+            }
+
+            let data = lock.read().await;
+            tokio::task::yield_now().await;
+            assert!(*data <= SIZE);
+            0
+        }));
+    }
+
+    let writes : usize = try_join_all(handles).await.unwrap().into_iter().sum();
+    let inner = Arc::try_unwrap(lock).unwrap();
+    assert_eq!(inner.into_inner(), writes);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancelled () {
+    let lock = Arc::new(RwLock::new(0));
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for _ in 0..SIZE {
+        let lock = lock.clone();
+        handles.push(tokio::spawn(async move {
+            let timeout = Duration::from_micros(rand::random::<u64>() % 100);
+            if rand::random::<bool>() {
+                let mut data = match tokio::time::timeout(timeout, lock.write()).await {
+                    Ok(data) => data,
+                    Err(_) => return 0
+                };
+
+                *data += 1;
+                tokio::task::yield_now().await;
+                return 1
+            }
+
+            if let Ok(data) = tokio::time::timeout(timeout, lock.read()).await {
+                tokio::task::yield_now().await;
+                drop(data);
+            }
+            0
+        }));
+    }
+
+    let writes : usize = try_join_all(handles).await.unwrap().into_iter().sum();
+    let inner = Arc::try_unwrap(lock).unwrap();
+    assert_eq!(inner.into_inner(), writes);
+}
+
+#[test]
+fn shared () {
+    let lock = RwLock::new(0);
+    let first = lock.try_read().unwrap();
+    let second = lock.try_read().unwrap();
+    assert!(lock.try_write().is_none());
+
+    drop(first);
+    assert!(lock.try_write().is_none());
+    drop(second);
+    assert!(lock.try_write().is_some());
+}
+
+#[test]
+fn writer_waits_for_readers () {
+    let lock = RwLock::new(0);
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let read = lock.try_read().unwrap();
+    let mut write = pin!(lock.write());
+    assert!(write.as_mut().poll(&mut cx).is_pending());
+
+    // a waiting writer keeps new readers out
+    assert!(lock.try_read().is_none());
+    drop(read);
+    assert!(write.poll(&mut cx).is_ready());
+}
+
+#[test]
+fn downgrade () {
+    let lock = RwLock::new(0);
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let mut write = lock.try_write().unwrap();
+    *write = 1;
+
+    let mut read = pin!(lock.read());
+    assert!(read.as_mut().poll(&mut cx).is_pending());
+
+    let guard = write.downgrade();
+    assert_eq!(*guard, 1);
+    assert!(lock.try_write().is_none());
+    assert!(read.poll(&mut cx).is_ready());
+}
+
+#[test]
+fn owned () {
+    let lock = std::rc::Rc::new(RwLock::new(0));
+    let mut write = lock.clone().try_write_owned().unwrap();
+    *write += 1;
+
+    let read = write.downgrade();
+    let other = lock.clone().try_read_owned().unwrap();
+    assert_eq!(*read + *other, 2);
+}
+
+#[test]
+fn third_party_pointer () {
+    let lock = triomphe::Arc::new(RwLock::new(0));
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let mut write = RwLock::try_write_ptr(lock.clone()).unwrap();
+    *write += 1;
+    assert!(RwLock::try_read_ptr(lock.clone()).is_none());
+
+    let mut read = pin!(RwLock::read_ptr(lock.clone()));
+    assert!(read.as_mut().poll(&mut cx).is_pending());
+    let downgraded = write.downgrade();
+    let std::task::Poll::Ready(other) = read.poll(&mut cx) else { panic!("the reader wasn't let in after the downgrade") };
+    assert_eq!(*downgraded + *other, 2);
+
+    let mut write = pin!(RwLock::write_ptr(lock.clone()));
+    assert!(write.as_mut().poll(&mut cx).is_pending());
+    drop((downgraded, other));
+    assert!(write.poll(&mut cx).is_ready());
+}
+
+#[test]
+fn movable_fused () {
+    let lock = MovableRwLock::new();
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let mut read = pin!(lock.read());
+    assert!(read.as_mut().poll(&mut cx).is_ready());
+    assert!(read.is_terminated());
+    unsafe { lock.unlock_read() }
+    assert!(lock.try_write());
+    unsafe { lock.unlock_write() }
+
+    let mut write = pin!(lock.write());
+    assert!(write.as_mut().poll(&mut cx).is_ready());
+    assert!(write.is_terminated());
+    unsafe { lock.unlock_write() }
+    assert!(lock.try_read());
+    unsafe { lock.unlock_read() }
+}
+
+#[test]
+#[should_panic(expected = "already consumed")]
+fn movable_repolled () {
+    let lock = MovableRwLock::new();
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let mut read = pin!(lock.read());
+    assert!(read.as_mut().poll(&mut cx).is_ready());
+    let _ = read.poll(&mut cx);
+}
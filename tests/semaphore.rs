@@ -0,0 +1,137 @@
+use std::{sync::{Arc, atomic::{AtomicUsize, Ordering}}, rc::Rc, thread, time::Duration, task::Context, pin::pin};
+use async_mutex::Semaphore;
+use futures::{Future, future::join_all, task::noop_waker_ref};
+
+const SIZE : usize = 1000;
+const PERMITS : usize = 4;
+
+#[test]
+fn permits () {
+    let semaphore = Semaphore::new(3);
+    let first = semaphore.try_acquire(2).unwrap();
+    assert_eq!(semaphore.available_permits(), 1);
+    assert!(semaphore.try_acquire(2).is_none());
+
+    drop(first);
+    assert_eq!(semaphore.available_permits(), 3);
+
+    semaphore.try_acquire(3).unwrap().forget();
+    assert_eq!(semaphore.available_permits(), 0);
+    semaphore.add_permits(1);
+    assert_eq!(semaphore.available_permits(), 1);
+}
+
+#[test]
+fn no_starvation () {
+    let semaphore = Semaphore::new(2);
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let permit = semaphore.try_acquire(1).unwrap();
+    let mut big = pin!(semaphore.acquire(2));
+    assert!(big.as_mut().poll(&mut cx).is_pending());
+
+    // there's a permit left, but the big request is queued before us
+    assert!(semaphore.try_acquire(1).is_none());
+    let mut small = pin!(semaphore.acquire(1));
+    assert!(small.as_mut().poll(&mut cx).is_pending());
+
+    drop(permit);
+    let big = match big.poll(&mut cx) {
+        std::task::Poll::Ready(permit) => permit,
+        std::task::Poll::Pending => panic!("permits weren't handed to the big request")
+    };
+
+    assert_eq!(big.permits(), 2);
+    assert!(small.as_mut().poll(&mut cx).is_pending());
+    drop(big);
+    assert!(small.poll(&mut cx).is_ready());
+}
+
+#[test]
+fn cancelled () {
+    let semaphore = Semaphore::new(1);
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let permit = semaphore.try_acquire(1).unwrap();
+    let mut big = Box::pin(semaphore.acquire(2));
+    let mut small = pin!(semaphore.acquire(1));
+    assert!(big.as_mut().poll(&mut cx).is_pending());
+    assert!(small.as_mut().poll(&mut cx).is_pending());
+
+    // the waiter that was blocking the queue gave up, so the one behind it can go through
+    drop(big);
+    drop(permit);
+    assert!(small.poll(&mut cx).is_ready());
+    assert_eq!(semaphore.available_permits(), 1);
+
+    // permits handed to a waiter that's dropped before being polled again are given back
+    let permit = semaphore.try_acquire(1).unwrap();
+    let mut fut = Box::pin(semaphore.acquire(1));
+    assert!(fut.as_mut().poll(&mut cx).is_pending());
+    drop(permit);
+    drop(fut);
+    assert_eq!(semaphore.available_permits(), 1);
+    assert!(semaphore.try_acquire(1).is_some());
+}
+
+#[test]
+fn owned () {
+    let semaphore = Rc::new(Semaphore::new(1));
+    let permit = semaphore.clone().try_acquire_owned(1).unwrap();
+    assert!(semaphore.clone().try_acquire_owned(1).is_none());
+    drop(permit);
+
+    semaphore.clone().acquire_blocking_owned(1).forget();
+    assert_eq!(semaphore.available_permits(), 0);
+}
+
+#[test]
+fn only_sync () {
+    let semaphore = Arc::new(Semaphore::new(PERMITS));
+    let current = Arc::new(AtomicUsize::new(0));
+    let mut handles = Vec::with_capacity(8);
+
+    for i in 0..8 {
+        let semaphore = semaphore.clone();
+        let current = current.clone();
+        handles.push(thread::spawn(move || {
+            let n = 1 + i % PERMITS;
+            for _ in 0..(SIZE/8) {
+                let _permit = semaphore.clone().acquire_blocking_atomic(n);
+                assert!(current.fetch_add(n, Ordering::SeqCst) + n <= PERMITS);
+                current.fetch_sub(n, Ordering::SeqCst);
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(semaphore.available_permits(), PERMITS);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn only_async () {
+    let semaphore = Arc::new(Semaphore::new(PERMITS));
+    let current = Arc::new(AtomicUsize::new(0));
+    let mut handles = Vec::with_capacity(SIZE);
+
+    for i in 0..SIZE {
+        let semaphore = semaphore.clone();
+        let current = current.clone();
+        handles.push(tokio::spawn(async move {
+            let n = 1 + i % PERMITS;
+            let fut = semaphore.acquire_atomic(n);
+            // some of the waiters give up before getting their permits
+            if let Ok(_permit) = tokio::time::timeout(Duration::from_micros(rand::random::<u64>() % 100), fut).await {
+                assert!(current.fetch_add(n, Ordering::SeqCst) + n <= PERMITS);
+                tokio::task::yield_now().await;
+                current.fetch_sub(n, Ordering::SeqCst);
+            }
+        }));
+    }
+
+    join_all(handles).await;
+    assert_eq!(semaphore.available_permits(), PERMITS);
+}
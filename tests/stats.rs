@@ -0,0 +1,74 @@
+use std::{sync::Arc, thread, time::Duration, task::Context, pin::pin};
+use async_mutex::{Mutex, MutexStats};
+use futures::{Future, task::noop_waker_ref};
+
+#[test]
+fn uncontended () {
+    let mutex = Mutex::new(0);
+    drop(mutex.try_lock().unwrap());
+    drop(mutex.try_lock().unwrap());
+
+    let stats = mutex.stats();
+    assert_eq!(stats.acquisitions, 2);
+    assert_eq!(stats.contended, 0);
+    assert_eq!(stats.max_queue_len, 0);
+}
+
+#[test]
+fn contended () {
+    let mutex = Mutex::new(0);
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let guard = mutex.try_lock().unwrap();
+    let mut first = pin!(mutex.lock());
+    let mut second = pin!(mutex.lock());
+    assert!(first.as_mut().poll(&mut cx).is_pending());
+    assert!(second.as_mut().poll(&mut cx).is_pending());
+    thread::sleep(Duration::from_millis(10));
+    drop(guard);
+
+    drop(first.poll(&mut cx));
+    drop(second.poll(&mut cx));
+
+    let stats = mutex.stats();
+    assert_eq!(stats.acquisitions, 3);
+    assert_eq!(stats.contended, 2);
+    assert_eq!(stats.async_waits, 2);
+    assert_eq!(stats.blocking_waits, 0);
+    assert_eq!(stats.max_queue_len, 2);
+    assert!(stats.max_hold >= Duration::from_millis(10));
+    assert!(stats.max_wait >= Duration::from_millis(10));
+    assert!(stats.total_wait >= stats.max_wait);
+}
+
+#[test]
+fn blocking () {
+    let mutex = Arc::new(Mutex::new(0));
+    let guard = mutex.try_lock().unwrap();
+
+    let handle = {
+        let mutex = mutex.clone();
+        thread::spawn(move || *mutex.lock_blocking() += 1)
+    };
+
+    while mutex.stats().blocking_waits == 0 {
+        thread::yield_now()
+    }
+
+    drop(guard);
+    handle.join().unwrap();
+
+    let stats = mutex.stats();
+    assert_eq!(stats.acquisitions, 2);
+    assert_eq!(stats.contended, 1);
+    assert_eq!(stats.blocking_waits, 1);
+}
+
+#[test]
+fn reset () {
+    let mutex = Mutex::new(0);
+    drop(mutex.try_lock().unwrap());
+
+    mutex.reset_stats();
+    assert_eq!(mutex.stats(), MutexStats::default());
+}
@@ -0,0 +1,86 @@
+use std::{sync::{Arc, Mutex as StdMutex}, task::Context, pin::pin, fmt::Debug};
+use async_mutex::Mutex;
+use futures::{Future, task::noop_waker_ref};
+use tracing::{Event, Metadata, Subscriber, field::{Field, Visit}, span::{Attributes, Id, Record}};
+
+/// Collects every event as it's message, followed by it's fields
+#[derive(Clone, Default)]
+struct Collector (Arc<StdMutex<Vec<String>>>);
+
+struct Fields (String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        match field.name() {
+            "message" => self.0.insert_str(0, &format!("{value:?}")),
+            name => self.0.push_str(&format!(" {name}={value:?}"))
+        }
+    }
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+    fn new_span(&self, _: &Attributes<'_>) -> Id { Id::from_u64(1) }
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(String::new());
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(fields.0);
+    }
+}
+
+impl Collector {
+    fn take (&self) -> Vec<String> {
+        core::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+#[test]
+fn blocking () {
+    let collector = Collector::default();
+    let mutex = Mutex::new(0).named("accounts");
+
+    tracing::subscriber::with_default(collector.clone(), || drop(mutex.lock_blocking()));
+    let events = collector.take();
+
+    assert_eq!(events.len(), 3, "{events:?}");
+    assert!(events[0].starts_with("lock requested mutex=accounts blocking=true"), "{events:?}");
+    assert!(events[1].starts_with("lock acquired mutex=accounts wait="), "{events:?}");
+    assert!(events[2].starts_with("lock released mutex=accounts hold="), "{events:?}");
+}
+
+#[test]
+fn contended () {
+    let collector = Collector::default();
+    let mutex = Mutex::new(0).named("accounts");
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    tracing::subscriber::with_default(collector.clone(), || {
+        let guard = mutex.try_lock().unwrap();
+        let mut fut = pin!(mutex.lock());
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+        drop(guard);
+        assert!(fut.poll(&mut cx).is_ready());
+    });
+
+    let events = collector.take().into_iter().map(|x| x.split(" wait=").next().unwrap().split(" hold=").next().unwrap().to_owned()).collect::<Vec<_>>();
+    assert_eq!(events, [
+        "lock acquired mutex=accounts",
+        "lock requested mutex=accounts blocking=false",
+        "lock contended mutex=accounts blocking=false",
+        "lock released mutex=accounts",
+        "lock acquired mutex=accounts",
+        "lock released mutex=accounts"
+    ]);
+}
+
+#[test]
+fn debug () {
+    let mutex = Mutex::new(0).named("accounts");
+    assert_eq!(format!("{mutex:?}"), "Mutex { name: \"accounts\", locked: false }");
+    assert_eq!(mutex.name(), Some("accounts"));
+}
@@ -0,0 +1,8 @@
+use std::cell::Cell;
+use async_mutex::guards::AtomicMutexGuard;
+
+fn sync<T: Sync> () {}
+
+fn main () {
+    sync::<AtomicMutexGuard<Cell<u8>>>();
+}
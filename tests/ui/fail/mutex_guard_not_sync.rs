@@ -0,0 +1,9 @@
+use std::cell::Cell;
+use async_mutex::guards::MutexGuard;
+
+fn sync<T: Sync> () {}
+
+fn main () {
+    // sharing the guard shares the data
+    sync::<MutexGuard<'static, Cell<u8>>>();
+}
@@ -0,0 +1,8 @@
+use std::rc::Rc;
+use async_mutex::Mutex;
+
+fn sync<T: Sync> () {}
+
+fn main () {
+    sync::<Mutex<Rc<u8>>>();
+}
@@ -0,0 +1,9 @@
+use async_mutex::guards::{OwnedMutexGuard, OwnedMutexFuture};
+
+fn send<T: Send> () {}
+
+fn main () {
+    // the mutex is kept alive by an `Rc`
+    send::<OwnedMutexGuard<u8>>();
+    send::<OwnedMutexFuture<u8>>();
+}
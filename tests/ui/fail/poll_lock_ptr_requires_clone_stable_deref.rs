@@ -0,0 +1,28 @@
+use std::{ops::Deref, pin::pin, task::Context};
+use async_mutex::{Mutex, StableDeref, guards::PtrMutexWaiter};
+use futures::task::noop_waker_ref;
+
+/// A handle whose clones box up a new mutex, so they don't point to the one it does
+struct Handle (Box<Mutex<u8>>);
+
+impl Clone for Handle {
+    fn clone(&self) -> Self {
+        Handle(Box::new(Mutex::new(*self.0.try_lock().unwrap())))
+    }
+}
+
+impl Deref for Handle {
+    type Target = Mutex<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+unsafe impl StableDeref for Handle {}
+
+fn main () {
+    let handle = Handle(Box::new(Mutex::new(0)));
+    let waiter = pin!(PtrMutexWaiter::new());
+    let _ = Mutex::poll_lock_ptr(&handle, waiter, &mut Context::from_waker(noop_waker_ref()));
+}
@@ -0,0 +1,12 @@
+use std::cell::Cell;
+use async_mutex::{ReentrantMutex, OwnerId, guards::ReentrantMutexGuard};
+
+fn send<T: Send> () {}
+
+fn main () {
+    // the thread that holds it may lock the mutex again
+    send::<ReentrantMutexGuard<'static, u8>>();
+    // tasks may lock it from different threads, so the data must be `Sync`
+    let mutex = ReentrantMutex::new(Cell::new(0));
+    let _ = mutex.lock(OwnerId::new());
+}
@@ -0,0 +1,9 @@
+use std::cell::Cell;
+use async_mutex::RwLock;
+
+fn sync<T: Sync> () {}
+
+fn main () {
+    // readers share the data between them
+    sync::<RwLock<Cell<u8>>>();
+}
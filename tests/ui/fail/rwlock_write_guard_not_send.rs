@@ -0,0 +1,9 @@
+use std::cell::Cell;
+use async_mutex::guards::RwLockWriteGuard;
+
+fn send<T: Send> () {}
+
+fn main () {
+    // it can be downgraded into a read guard on another thread
+    send::<RwLockWriteGuard<'static, Cell<u8>>>();
+}
@@ -0,0 +1,45 @@
+use std::{cell::Cell, sync::MutexGuard as StdGuard};
+use async_mutex::{Mutex, RwLock, ReentrantMutex, Semaphore, Condvar, movable::{MovableMutex, MovableRwLock}, guards::*};
+
+fn send<T: ?Sized + Send> () {}
+fn sync<T: ?Sized + Sync> () {}
+
+fn main () {
+    // a mutex only needs it's data to be `Send`, even to be shared
+    send::<Mutex<Cell<u8>>>();
+    sync::<Mutex<Cell<u8>>>();
+    sync::<Mutex<[u8]>>();
+    send::<MutexGuard<'static, Cell<u8>>>();
+    send::<MutexFuture<'static, Cell<u8>>>();
+    send::<AtomicMutexGuard<Cell<u8>>>();
+    send::<AtomicMutexFuture<Cell<u8>>>();
+    send::<MappedMutexGuard<'static, Cell<u8>>>();
+    send::<AtomicMutexWaiter<Cell<u8>>>();
+
+    // readers only need the data to be `Sync`
+    sync::<RwLock<u8>>();
+    send::<RwLockReadGuard<'static, StdGuard<'static, u8>>>();
+    sync::<RwLockReadGuard<'static, StdGuard<'static, u8>>>();
+    send::<RwLockReadFuture<'static, StdGuard<'static, u8>>>();
+    sync::<RwLockWriteGuard<'static, StdGuard<'static, u8>>>();
+
+    // like a regular mutex, a reentrant one only needs it's data to be `Send`
+    sync::<ReentrantMutex<Cell<u8>>>();
+    send::<ReentrantMutexTaskGuard<'static, u8>>();
+    send::<ReentrantMutexFuture<'static, u8>>();
+    sync::<ReentrantMutexGuard<'static, u8>>();
+
+    send::<MovableMutex>();
+    sync::<MovableMutex>();
+    sync::<MovableRwLock>();
+    // lock tokens can be moved to (and unlock from) another thread
+    send::<MovableMutexGuard<'static>>();
+    sync::<MovableMutexGuard<'static>>();
+    send::<MovableMutexFuture<'static>>();
+    send::<AtomicMovableMutexGuard>();
+    send::<AtomicMovableMutexFuture>();
+    sync::<Semaphore>();
+    sync::<Condvar>();
+    send::<SemaphorePermit<'static>>();
+    send::<SemaphoreFuture<'static>>();
+}
@@ -0,0 +1,12 @@
+use std::panic::{UnwindSafe, RefUnwindSafe};
+use async_mutex::{Semaphore, Condvar, movable::{MovableMutex, MovableRwLock}};
+
+fn unwind_safe<T: ?Sized + UnwindSafe + RefUnwindSafe> () {}
+
+fn main () {
+    // the locks' own state is consistent whenever a panic can happen
+    unwind_safe::<MovableMutex>();
+    unwind_safe::<MovableRwLock>();
+    unwind_safe::<Semaphore>();
+    unwind_safe::<Condvar>();
+}